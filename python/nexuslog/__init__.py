@@ -6,10 +6,23 @@ Usage:
     logging.info("Hello, world!")
 """
 
+import atexit as _atexit
+import logging as _stdlib_logging
+from typing import Any, TextIO
+
 from ._logger import (
     PyLevel as Level,
+    PyFormat as Format,
+    PyCompression as Compression,
+    PyRotation as Rotation,
+    PyOverflow as Overflow,
+    PySink as Sink,
     PyLogger as _PyLogger,
+    PyHandler as _PyHandler,
     get_logger as _get_logger,
+    set_level as _set_level,
+    forward_rust_log as _forward_rust_log,
+    flush_all as _flush_all,
     basic_config as _basic_config,
 )
 
@@ -19,29 +32,46 @@ DEBUG = Level.Debug
 INFO = Level.Info
 WARNING = Level.Warn
 ERROR = Level.Error
+CRITICAL = Level.Critical
+FATAL = CRITICAL
 
 __all__ = [
     "Level",
+    "Format",
+    "Compression",
+    "Rotation",
+    "Overflow",
+    "Sink",
     "Logger",
+    "Handler",
     "basicConfig",
     "getLogger",
+    "setLevel",
+    "capture_stdlib_logging",
+    "forward_rust_log",
     "TRACE",
     "DEBUG",
     "INFO",
     "WARNING",
     "ERROR",
+    "CRITICAL",
+    "FATAL",
     "trace",
     "debug",
     "info",
     "warning",
     "error",
+    "exception",
+    "critical",
+    "log",
     "shutdown",
 ]
 
-_DEFAULT_LEVEL = INFO
-_NAME_LEVELS: dict[str | None, Level] = {}
 _root_logger: "Logger | None" = None
 
+# records still buffered or queued at exit are written, and drops reported
+_atexit.register(_flush_all)
+
 
 def basicConfig(
     filename: str | None = None,
@@ -49,24 +79,103 @@ def basicConfig(
     name_levels: dict[str | None, Level] | None = None,
     unix_ts: bool = False,
     batch_size: int | None = None,
+    fmt: Format = Format.Logfmt,
+    escape: bool = True,
+    pattern: str | None = None,
+    max_bytes: int | None = None,
+    max_files: int | None = None,
+    max_age_days: int | None = None,
+    max_total_bytes: int | None = None,
+    compression: Compression | None = None,
+    rotation: Rotation = Rotation.Daily,
+    rotation_minutes: int = 1,
+    overflow: Overflow = Overflow.Block,
+    max_latency_ms: int | None = None,
+    sinks: list[Sink] | None = None,
+    stream: TextIO | None = None,
 ) -> None:
     """Configure the root logger.
 
     Args:
         filename: Optional file path for log output. If None, logs to stdout.
         level: Minimum log level to record. Default is INFO.
-        name_levels: Optional per-name level overrides. Exact match only.
-                     When provided, it takes priority over the global level.
+        name_levels: Optional per-name levels. A name also covers the dotted
+                     names below it, so {"http": DEBUG} applies to
+                     "http.client" unless that has a level of its own.
         unix_ts: If True, emit unix timestamps instead of formatted local time.
         batch_size: Number of log entries to batch before writing. Default is 32.
                     Set to 1 to write immediately (lower performance, no data loss on crash).
+        fmt: Output format. Format.Logfmt (default) writes `key=value` lines,
+             Format.Json writes one JSON object per line.
+        escape: If True (default), quotes, backslashes and control characters in
+                `msg` and `name` are escaped so every record stays on one line.
+                Set to False to write logfmt messages verbatim. JSON output is
+                always escaped.
+        pattern: Optional line template, e.g.
+                 "{time:%H:%M:%S%.6f} [{level:>5}] {name}: {msg}". Fields are
+                 {time}, {level}, {name} and {msg}; {time:...} takes a strftime
+                 spec, the others an alignment and width. Overrides `fmt`.
+                 Raises ValueError if the template is invalid.
+        max_bytes: Optional size limit per file. When reached, the writer moves on
+                   to {stem}_YYYYMMDD.1.{ext}, {stem}_YYYYMMDD.2.{ext}, ... on top
+                   of the daily rotation.
+        max_files: Optional number of log files to keep, including the current one.
+        max_age_days: Optional age limit; files dated more than this many days
+                      ago are deleted.
+        max_total_bytes: Optional cap on the combined size of all log files.
+                         Retention is enforced after each rotation, oldest files
+                         first, and only touches files named after `filename`.
+        compression: Optional Compression.Gzip or Compression.Zstd. Files the
+                     writer has rotated away from are compressed on a background
+                     thread into {file}.gz / {file}.zst, replacing the plain file.
+        rotation: How often a new file is started. Rotation.Daily (default) names
+                  files {stem}_YYYYMMDD.{ext}, Rotation.Hourly {stem}_YYYYMMDD_HH.{ext},
+                  Rotation.Minutely {stem}_YYYYMMDD_HHMM.{ext}, Rotation.Weekly
+                  {stem}_YYYYWww.{ext} and Rotation.Never writes to {stem}.{ext}.
+        rotation_minutes: Period length for Rotation.Minutely, counted from midnight.
+        overflow: What a logging call does when the writer queue is full.
+                  Overflow.Block (default) waits, Overflow.DropNewest discards the
+                  batch being sent and Overflow.DropOldest discards the oldest
+                  queued one. Discarded records are counted and reported in the
+                  log as a warning from the "nexuslog" logger.
+        max_latency_ms: Optional upper bound, in milliseconds, on how long a record
+                        may wait in a partially filled batch. Without it a thread
+                        that stops logging keeps its last records until the batch
+                        fills or the logger shuts down.
+        sinks: Optional extra outputs, each with its own level and format, e.g.
+               [Sink(level=WARNING), Sink("/var/log/app.json", fmt=Format.Json)].
+               They are written by the same thread from the same records,
+               after the logger levels; file sinks use the rotation, size and
+               retention settings above.
+        stream: sys.stdout or sys.stderr, instead of `filename`. Other streams
+                raise TypeError.
     """
-    global _DEFAULT_LEVEL, _NAME_LEVELS, _root_logger
-    _basic_config(filename, unix_ts, batch_size)
-    _DEFAULT_LEVEL = level
-    _NAME_LEVELS = {} if name_levels is None else dict(name_levels)
+    global _root_logger
+    if stream is not None and filename is not None:
+        raise ValueError("'stream' and 'filename' should not be specified together")
+    _basic_config(
+        filename if stream is None else stream,
+        unix_ts,
+        batch_size,
+        fmt,
+        escape,
+        pattern,
+        max_bytes,
+        max_files,
+        max_age_days,
+        max_total_bytes,
+        compression,
+        rotation,
+        rotation_minutes,
+        overflow,
+        max_latency_ms,
+        sinks,
+    )
+    _set_level(None, level)
+    for name, name_level in (name_levels or {}).items():
+        _set_level(name, name_level)
     # Create root logger
-    _root_logger = getLogger(None, level)
+    _root_logger = getLogger(None)
 
 
 class Logger:
@@ -76,11 +185,32 @@ class Logger:
         name: Logger name. If None, the name field is omitted in log output.
         path: Optional file path prefix for log files. If None, logs to stdout.
               Log files are rotated daily with format: {path}_YYYYMMDD.log
+              sys.stdout and sys.stderr select those streams.
         level: Minimum log level to record. Default is Level.Info.
+
+    The logging methods ``trace``, ``debug``, ``info``, ``warning``, ``error``,
+    ``exception``, ``critical`` and ``log(level, ...)`` take
+    ``(message, *args, exc_info=None, stack_info=False, extra=None, **kwargs)``.
+
+    Positional arguments are merged into the message with ``%`` formatting, as
+    in the standard library, only when the level is enabled. Arguments that do
+    not fit the message never raise; the message is written unformatted with
+    the error as a ``format_error`` field.
+
+    Keyword arguments and ``extra={...}`` passed to the logging methods are
+    written as fields of the record. Ints, floats, strings and bools keep their
+    type in JSON output, other values are converted with ``str()``. A key the
+    record writes itself raises KeyError; ``stacklevel`` is ignored.
+
+    ``exc_info`` and ``stack_info`` work as in the standard library. The
+    traceback and stack are written as ``exc`` and ``stack`` fields.
     """
 
     def __init__(
-        self, name: str | None, path: str | None = None, level: Level = Level.Info
+        self,
+        name: str | None,
+        path: str | TextIO | None = None,
+        level: Level | int = Level.Info,
     ) -> None:
         self._logger = _PyLogger(name, path, level)
 
@@ -88,70 +218,176 @@ class Logger:
         """Shutdown the logger and flush remaining messages."""
         self._logger.shutdown()
 
-    def trace(self, message: str) -> None:
+    def setLevel(self, level: Level | int) -> None:
+        """Set the minimum level of this logger."""
+        self._logger.setLevel(level)
+
+    def getEffectiveLevel(self) -> Level:
+        """Return the minimum level of this logger."""
+        return self._logger.getEffectiveLevel()
+
+    def isEnabledFor(self, level: Level | int) -> bool:
+        """Return whether a record at `level` would be written."""
+        return self._logger.isEnabledFor(level)
+
+    def trace(self, message: object, *args: object, **kwargs: Any) -> None:
         """Log a trace message."""
-        self._logger.trace(message)
+        self._logger.trace(message, *args, **kwargs)
 
-    def debug(self, message: str) -> None:
+    def debug(self, message: object, *args: object, **kwargs: Any) -> None:
         """Log a debug message."""
-        self._logger.debug(message)
+        self._logger.debug(message, *args, **kwargs)
 
-    def info(self, message: str) -> None:
+    def info(self, message: object, *args: object, **kwargs: Any) -> None:
         """Log an info message."""
-        self._logger.info(message)
+        self._logger.info(message, *args, **kwargs)
 
-    def warning(self, message: str) -> None:
+    def warning(self, message: object, *args: object, **kwargs: Any) -> None:
         """Log a warning message."""
-        self._logger.warn(message)
+        self._logger.warn(message, *args, **kwargs)
 
-    def error(self, message: str) -> None:
+    def error(self, message: object, *args: object, **kwargs: Any) -> None:
         """Log an error message."""
-        self._logger.error(message)
+        self._logger.error(message, *args, **kwargs)
+
+    def exception(self, message: object, *args: object, **kwargs: Any) -> None:
+        """Log an error message with the exception being handled."""
+        self._logger.exception(message, *args, **kwargs)
 
+    def critical(self, message: object, *args: object, **kwargs: Any) -> None:
+        """Log a critical message."""
+        self._logger.critical(message, *args, **kwargs)
 
-def getLogger(name: str | None = None, level: Level | None = None) -> Logger:
-    """Get a logger that shares a writer with the default path."""
-    if level is None:
-        if name in _NAME_LEVELS:
-            level = _NAME_LEVELS[name]
-        else:
-            level = _DEFAULT_LEVEL
+    fatal = critical
+
+    def log(self, level: Level | int, message: object, *args: object, **kwargs: Any) -> None:
+        """Log a message at a Level or a standard library numeric level."""
+        self._logger.log(level, message, *args, **kwargs)
+
+
+def getLogger(name: str | None = None, level: Level | int | None = None) -> Logger:
+    """Get the logger called `name`, sharing a writer with the default path.
+
+    Loggers are cached per name. Dotted names form a hierarchy: without a level
+    of its own, "http.client" follows "http", which follows the root logger.
+    """
     logger = Logger.__new__(Logger)
     logger._logger = _get_logger(name, level)
     return logger
 
 
+
+def setLevel(name: str | None, level: Level | int) -> None:
+    """Set the level of the logger called `name`, which the loggers below it
+    follow unless they have a level of their own."""
+    _set_level(name, level)
+
+
+class Handler(_stdlib_logging.Handler):
+    """Standard library logging handler that writes through nexuslog.
+
+    Records keep their logger name, level, creation time and ``getMessage()``
+    text; tracebacks and stacks become ``exc`` and ``stack`` fields. The
+    output is the one set by basicConfig, or `path` if given. Formatters set on
+    the handler are not used.
+    """
+
+    def __init__(
+        self, level: int = _stdlib_logging.NOTSET, path: str | TextIO | None = None
+    ) -> None:
+        super().__init__(level)
+        self._handler = _PyHandler(path)
+
+    def emit(self, record: _stdlib_logging.LogRecord) -> None:
+        try:
+            self._handler.emit(record)
+        except Exception:
+            self.handleError(record)
+
+    def flush(self) -> None:
+        self._handler.flush()
+
+
+def capture_stdlib_logging(level: int | None = None) -> Handler:
+    """Send all standard library logging through nexuslog.
+
+    Replaces the handlers of the stdlib root logger with a single Handler,
+    and sets the root logger's level if `level` is given. Call it after
+    basicConfig. Returns the installed handler.
+    """
+    root = _stdlib_logging.getLogger()
+    for handler in root.handlers[:]:
+        root.removeHandler(handler)
+        handler.close()
+    handler = Handler()
+    root.addHandler(handler)
+    if level is not None:
+        root.setLevel(level)
+    return handler
+
+
+def forward_rust_log(level: Level | int | str | None = None) -> None:
+    """Write records of the Rust `log` crate to the output set by basicConfig.
+
+    Rust code in extension modules that logs with ``log::info!`` then shares
+    one ordered log with Python. Records are named after their Rust target.
+    `level` is a Level, a stdlib number or directives such as
+    "info,hyper=warn"; by default NEXUSLOG or RUST_LOG is read. Raises
+    ValueError for invalid directives and RuntimeError if another Rust
+    logger is installed already.
+    """
+    _forward_rust_log(level)
+
+
 def _get_root_logger() -> Logger:
     """Get or create the root logger."""
     global _root_logger
     if _root_logger is None:
-        _root_logger = getLogger(None, _DEFAULT_LEVEL)
+        _root_logger = getLogger(None)
     return _root_logger
 
 
-def trace(message: str) -> None:
+def trace(message: object, *args: object, **kwargs: Any) -> None:
     """Log a trace message to the root logger."""
-    _get_root_logger().trace(message)
+    _get_root_logger().trace(message, *args, **kwargs)
 
 
-def debug(message: str) -> None:
+def debug(message: object, *args: object, **kwargs: Any) -> None:
     """Log a debug message to the root logger."""
-    _get_root_logger().debug(message)
+    _get_root_logger().debug(message, *args, **kwargs)
 
 
-def info(message: str) -> None:
+def info(message: object, *args: object, **kwargs: Any) -> None:
     """Log an info message to the root logger."""
-    _get_root_logger().info(message)
+    _get_root_logger().info(message, *args, **kwargs)
 
 
-def warning(message: str) -> None:
+def warning(message: object, *args: object, **kwargs: Any) -> None:
     """Log a warning message to the root logger."""
-    _get_root_logger().warning(message)
+    _get_root_logger().warning(message, *args, **kwargs)
 
 
-def error(message: str) -> None:
+def error(message: object, *args: object, **kwargs: Any) -> None:
     """Log an error message to the root logger."""
-    _get_root_logger().error(message)
+    _get_root_logger().error(message, *args, **kwargs)
+
+
+def exception(message: object, *args: object, **kwargs: Any) -> None:
+    """Log an error message with the exception being handled to the root logger."""
+    _get_root_logger().exception(message, *args, **kwargs)
+
+
+def critical(message: object, *args: object, **kwargs: Any) -> None:
+    """Log a critical message to the root logger."""
+    _get_root_logger().critical(message, *args, **kwargs)
+
+
+fatal = critical
+
+
+def log(level: Level | int, message: object, *args: object, **kwargs: Any) -> None:
+    """Log a message at a Level or a standard library numeric level to the root logger."""
+    _get_root_logger().log(level, message, *args, **kwargs)
 
 
 def shutdown() -> None:
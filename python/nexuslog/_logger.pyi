@@ -1,4 +1,7 @@
+from collections.abc import Mapping
 from enum import Enum
+from logging import LogRecord
+from typing import Any, TextIO
 
 class PyLevel(Enum):
     Trace: PyLevel
@@ -6,17 +9,145 @@ class PyLevel(Enum):
     Info: PyLevel
     Warn: PyLevel
     Error: PyLevel
+    Critical: PyLevel
+
+class PyFormat(Enum):
+    Logfmt: PyFormat
+    Json: PyFormat
+
+class PyCompression(Enum):
+    Gzip: PyCompression
+    Zstd: PyCompression
+
+class PyRotation(Enum):
+    Never: PyRotation
+    Minutely: PyRotation
+    Hourly: PyRotation
+    Daily: PyRotation
+    Weekly: PyRotation
+
+class PyOverflow(Enum):
+    Block: PyOverflow
+    DropNewest: PyOverflow
+    DropOldest: PyOverflow
+
+class PySink:
+    def __init__(
+        self,
+        path: str | TextIO | None = None,
+        level: PyLevel | int | None = None,
+        fmt: PyFormat = PyFormat.Logfmt,
+        pattern: str | None = None,
+        unix_ts: bool = False,
+        escape: bool = True,
+    ) -> None: ...
 
 class PyLogger:
     def __init__(
-        self, name: str | None, path: str | None = None, level: PyLevel = PyLevel.Info
+        self, name: str | None, path: str | TextIO | None = None, level: PyLevel | int = PyLevel.Info
     ) -> None: ...
     def shutdown(self) -> None: ...
-    def trace(self, message: str) -> None: ...
-    def debug(self, message: str) -> None: ...
-    def info(self, message: str) -> None: ...
-    def warn(self, message: str) -> None: ...
-    def error(self, message: str) -> None: ...
-
-def basic_config(path: str | None = None, unix_ts: bool = False) -> None: ...
-def get_logger(name: str | None, level: PyLevel = PyLevel.Info) -> PyLogger: ...
+    def setLevel(self, level: PyLevel | int) -> None: ...
+    def getEffectiveLevel(self) -> PyLevel: ...
+    def isEnabledFor(self, level: PyLevel | int) -> bool: ...
+    def trace(
+        self,
+        message: object,
+        *args: object,
+        exc_info: object = None,
+        stack_info: bool = False,
+        extra: Mapping[str, Any] | None = None,
+        **kwargs: Any,
+    ) -> None: ...
+    def debug(
+        self,
+        message: object,
+        *args: object,
+        exc_info: object = None,
+        stack_info: bool = False,
+        extra: Mapping[str, Any] | None = None,
+        **kwargs: Any,
+    ) -> None: ...
+    def info(
+        self,
+        message: object,
+        *args: object,
+        exc_info: object = None,
+        stack_info: bool = False,
+        extra: Mapping[str, Any] | None = None,
+        **kwargs: Any,
+    ) -> None: ...
+    def warn(
+        self,
+        message: object,
+        *args: object,
+        exc_info: object = None,
+        stack_info: bool = False,
+        extra: Mapping[str, Any] | None = None,
+        **kwargs: Any,
+    ) -> None: ...
+    def error(
+        self,
+        message: object,
+        *args: object,
+        exc_info: object = None,
+        stack_info: bool = False,
+        extra: Mapping[str, Any] | None = None,
+        **kwargs: Any,
+    ) -> None: ...
+    def exception(
+        self,
+        message: object,
+        *args: object,
+        exc_info: object = None,
+        stack_info: bool = False,
+        extra: Mapping[str, Any] | None = None,
+        **kwargs: Any,
+    ) -> None: ...
+    def critical(
+        self,
+        message: object,
+        *args: object,
+        exc_info: object = None,
+        stack_info: bool = False,
+        extra: Mapping[str, Any] | None = None,
+        **kwargs: Any,
+    ) -> None: ...
+    def log(
+        self,
+        level: PyLevel | int,
+        message: object,
+        *args: object,
+        exc_info: object = None,
+        stack_info: bool = False,
+        extra: Mapping[str, Any] | None = None,
+        **kwargs: Any,
+    ) -> None: ...
+
+class PyHandler:
+    def __init__(self, path: str | TextIO | None = None) -> None: ...
+    def emit(self, record: LogRecord) -> None: ...
+    def flush(self) -> None: ...
+
+def basic_config(
+    path: str | TextIO | None = None,
+    unix_ts: bool = False,
+    batch_size: int | None = None,
+    fmt: PyFormat = PyFormat.Logfmt,
+    escape: bool = True,
+    pattern: str | None = None,
+    max_bytes: int | None = None,
+    max_files: int | None = None,
+    max_age_days: int | None = None,
+    max_total_bytes: int | None = None,
+    compression: PyCompression | None = None,
+    rotation: PyRotation = PyRotation.Daily,
+    rotation_minutes: int = 1,
+    overflow: PyOverflow = PyOverflow.Block,
+    max_latency_ms: int | None = None,
+    sinks: list[PySink] | None = None,
+) -> None: ...
+def get_logger(name: str | None, level: PyLevel | int | None = None) -> PyLogger: ...
+def set_level(name: str | None, level: PyLevel | int) -> None: ...
+def forward_rust_log(level: PyLevel | int | str | None = None) -> None: ...
+def flush_all() -> None: ...
@@ -1,3 +1,6 @@
+import logging as stdlib_logging
+import time
+
 import nexuslog as logging
 
 
@@ -6,6 +9,18 @@ def _read_file(path: str) -> str:
         return f.read()
 
 
+def _read_logs(tmp_path, stem: str = "nexuslog_test", timeout: float = 2.0) -> str:
+    """Read every rotated file for `stem`, waiting for the writer to flush."""
+    deadline = time.monotonic() + timeout
+    while True:
+        contents = "".join(
+            _read_file(str(p)) for p in sorted(tmp_path.glob(f"{stem}*"))
+        )
+        if contents.endswith("\n") or time.monotonic() >= deadline:
+            return contents
+        time.sleep(0.01)
+
+
 def test_name_levels_override_default(tmp_path) -> None:
     path = tmp_path / "nexuslog_test.log"
     logging.basicConfig(
@@ -43,3 +58,697 @@ def test_explicit_level_overrides_name_levels(tmp_path) -> None:
     contents = _read_file(str(path))
     assert "explicit-warn" in contents
     assert "explicit-debug" not in contents
+
+
+def test_json_format_writes_one_object_per_line(tmp_path) -> None:
+    import json
+
+    path = tmp_path / "nexuslog_test.log"
+    logging.basicConfig(
+        filename=str(path),
+        level=logging.INFO,
+        batch_size=1,
+        fmt=logging.Format.Json,
+    )
+    logger = logging.getLogger("json")
+
+    logger.info('quote " backslash \\ newline \n tab \t end')
+    logger.shutdown()
+
+    lines = _read_logs(tmp_path).splitlines()
+    assert len(lines) == 1
+    record = json.loads(lines[0])
+    assert record["level"] == "info"
+    assert record["name"] == "json"
+    assert record["msg"] == 'quote " backslash \\ newline \n tab \t end'
+    assert "time" in record
+
+
+def _parse_logfmt(line: str) -> dict[str, str]:
+    import json
+    import re
+
+    fields = {}
+    for key, value in re.findall(r'(\w+)=("(?:[^"\\]|\\.)*"|\S*)', line):
+        fields[key] = json.loads(value) if value.startswith('"') else value
+    return fields
+
+
+def test_logfmt_escapes_hostile_strings(tmp_path) -> None:
+    path = tmp_path / "nexuslog_test.log"
+    logging.basicConfig(filename=str(path), level=logging.INFO, batch_size=1)
+    hostile = [
+        'say "hi"',
+        "back\\slash",
+        "Traceback:\n  line 1\n  line 2",
+        "cr\rtab\tbell\x07",
+        'msg="fake" level=error',
+        "",
+    ]
+    logger = logging.getLogger('we ird="name"')
+    for message in hostile:
+        logger.info(message)
+    logger.shutdown()
+
+    lines = _read_logs(tmp_path).splitlines()
+    assert len(lines) == len(hostile)
+    for line, message in zip(lines, hostile):
+        fields = _parse_logfmt(line)
+        assert fields["level"] == "info"
+        assert fields["name"] == 'we ird="name"'
+        assert fields["msg"] == message
+
+
+def test_logfmt_raw_output_when_escape_disabled(tmp_path) -> None:
+    path = tmp_path / "nexuslog_test.log"
+    logging.basicConfig(filename=str(path), batch_size=1, escape=False)
+    logger = logging.getLogger("raw")
+    logger.info('say "hi"')
+    logger.shutdown()
+
+    assert _read_logs(tmp_path).endswith(' name=raw msg="say "hi""\n')
+
+
+def test_pattern_layout(tmp_path) -> None:
+    import re
+
+    path = tmp_path / "nexuslog_test.log"
+    logging.basicConfig(
+        filename=str(path),
+        batch_size=1,
+        pattern="{time:%H:%M:%S%.6f} [{level:>5}] {name}: {msg} {{done}}",
+    )
+    logger = logging.getLogger("legacy")
+    logger.info("first\nsecond")
+    logger.shutdown()
+
+    line = _read_logs(tmp_path)
+    assert re.fullmatch(
+        r"\d\d:\d\d:\d\d\.\d{6} \[ info\] legacy: first\\nsecond \{done\}\n", line
+    ), line
+
+
+def test_invalid_pattern_raises(tmp_path) -> None:
+    path = tmp_path / "nexuslog_test.log"
+    for pattern in ["{bogus}", "{msg", "msg}", "{level:>x}"]:
+        try:
+            logging.basicConfig(filename=str(path), pattern=pattern)
+        except ValueError:
+            continue
+        raise AssertionError(f"{pattern!r} was accepted")
+
+
+def test_max_bytes_rolls_to_numbered_files(tmp_path) -> None:
+    path = tmp_path / "nexuslog_test.log"
+    logging.basicConfig(filename=str(path), batch_size=1, max_bytes=200)
+    logger = logging.getLogger("size")
+    for i in range(20):
+        logger.info(f"message {i:02d}")
+    logger.shutdown()
+
+    contents = _read_logs(tmp_path)
+    files = sorted(p.name for p in tmp_path.glob("nexuslog_test_*.log"))
+    assert len(files) > 1
+    assert any(name.endswith(".1.log") for name in files)
+    for i in range(20):
+        assert f"message {i:02d}" in contents
+
+
+def test_retention_keeps_only_own_recent_files(tmp_path) -> None:
+    old = [
+        "nexuslog_test_20200101.log",
+        "nexuslog_test_20200102.log",
+        "nexuslog_test_20200102.1.log",
+        "nexuslog_test_20200103.log",
+    ]
+    unrelated = ["other_20200101.log", "nexuslog_test_backup.log"]
+    for name in old + unrelated:
+        (tmp_path / name).write_text("x\n")
+
+    path = tmp_path / "nexuslog_test.log"
+    logging.basicConfig(filename=str(path), batch_size=1, max_files=3)
+    logger = logging.getLogger("retention")
+    logger.info("hello")
+    logger.shutdown()
+    _read_logs(tmp_path, stem=f"nexuslog_test_{time.strftime('%Y%m%d')}")
+
+    remaining = sorted(p.name for p in tmp_path.iterdir())
+    assert "nexuslog_test_20200103.log" in remaining
+    assert "nexuslog_test_20200102.1.log" in remaining
+    assert "nexuslog_test_20200102.log" not in remaining
+    assert "nexuslog_test_20200101.log" not in remaining
+    for name in unrelated:
+        assert name in remaining
+
+
+def test_rotated_files_are_gzipped(tmp_path) -> None:
+    import gzip
+
+    path = tmp_path / "nexuslog_test.log"
+    logging.basicConfig(
+        filename=str(path),
+        batch_size=1,
+        max_bytes=200,
+        compression=logging.Compression.Gzip,
+    )
+    logger = logging.getLogger("gzip")
+    for i in range(20):
+        logger.info(f"message {i:02d}")
+    logger.shutdown()
+    _read_logs(tmp_path)
+
+    # rotated files are compressed in the background, wait for the .gz to settle
+    deadline = time.monotonic() + 2.0
+    while True:
+        try:
+            compressed = [
+                gzip.decompress(p.read_bytes()).decode()
+                for p in sorted(tmp_path.glob("*.log.gz"))
+            ]
+            plain = [_read_file(str(p)) for p in sorted(tmp_path.glob("*.log"))]
+        except (OSError, EOFError):
+            compressed, plain = [], []
+        if len(plain) == 1 and compressed and not list(tmp_path.glob("*.tmp")):
+            break
+        assert time.monotonic() < deadline, sorted(p.name for p in tmp_path.iterdir())
+        time.sleep(0.01)
+
+    contents = "".join(compressed + plain)
+    for i in range(20):
+        assert f"message {i:02d}" in contents
+
+
+def test_late_records_go_to_current_file(tmp_path) -> None:
+    path = tmp_path / "nexuslog_test.log"
+    logging.basicConfig(filename=str(path), batch_size=1, compression=logging.Compression.Gzip)
+    handler = logging.Handler()
+    now = time.time()
+    messages = ["today-1", "yday-A", "today-2", "yday-B", "today-3"]
+    for msg in messages:
+        record = stdlib_logging.LogRecord("late", stdlib_logging.INFO, __file__, 0, msg, None, None)
+        record.created = now - 86400 if msg.startswith("yday") else now
+        handler.emit(record)
+    handler.flush()
+
+    contents = _read_logs(tmp_path)
+    assert [_parse_logfmt(line)["msg"] for line in contents.splitlines()] == messages
+    today = time.strftime("%Y%m%d", time.localtime(now))
+    assert [p.name for p in tmp_path.iterdir()] == [f"nexuslog_test_{today}.log"]
+
+
+def test_existing_compressed_file_is_kept(tmp_path) -> None:
+    import gzip
+
+    path = tmp_path / "nexuslog_test.log"
+    yesterday = time.time() - 86400
+    old = tmp_path / f"nexuslog_test_{time.strftime('%Y%m%d', time.localtime(yesterday))}.log.gz"
+    old.write_bytes(gzip.compress(b"archived\n"))
+    logging.basicConfig(filename=str(path), batch_size=1, compression=logging.Compression.Gzip)
+    handler = logging.Handler()
+    for msg, created in [("late", yesterday), ("current", time.time())]:
+        record = stdlib_logging.LogRecord("late", stdlib_logging.INFO, __file__, 0, msg, None, None)
+        record.created = created
+        handler.emit(record)
+    handler.flush()
+    time.sleep(0.2)
+
+    assert gzip.decompress(old.read_bytes()) == b"archived\n"
+    contents = "".join(_read_file(str(p)) for p in tmp_path.glob("*.log"))
+    assert "late" in contents and "current" in contents
+
+
+def test_rotation_interval_sets_file_postfix(tmp_path) -> None:
+    import re
+
+    cases = [
+        (logging.Rotation.Hourly, 1, r"nexuslog_test_\d{8}_\d{2}\.log"),
+        (logging.Rotation.Minutely, 15, r"nexuslog_test_\d{8}_\d{2}(00|15|30|45)\.log"),
+        (logging.Rotation.Weekly, 1, r"nexuslog_test_\d{4}W\d{2}\.log"),
+        (logging.Rotation.Never, 1, r"nexuslog_test\.log"),
+    ]
+    for i, (rotation, minutes, expected) in enumerate(cases):
+        directory = tmp_path / str(i)
+        directory.mkdir()
+        path = directory / "nexuslog_test.log"
+        logging.basicConfig(
+            filename=str(path),
+            batch_size=1,
+            rotation=rotation,
+            rotation_minutes=minutes,
+        )
+        logger = logging.getLogger("rotation")
+        logger.info("hello")
+        logger.shutdown()
+
+        assert "hello" in _read_logs(directory)
+        names = [p.name for p in directory.iterdir()]
+        assert len(names) == 1 and re.fullmatch(expected, names[0]), names
+
+
+def test_drop_newest_reports_discarded_records() -> None:
+    import os
+    import re
+    import subprocess
+    import sys
+
+    # stdout is a pipe nobody reads until the child has logged everything, so
+    # the writer queue fills up; it says so on stderr
+    script = """
+import sys
+import nexuslog as logging
+logging.basicConfig(batch_size=1, overflow=logging.Overflow.DropNewest)
+logger = logging.getLogger("overflow")
+for i in range(150_000):
+    logger.info("m")
+print("logged", file=sys.stderr, flush=True)
+logging.shutdown()
+"""
+    env = dict(os.environ)
+    package_dir = os.path.dirname(os.path.dirname(logging.__file__))
+    env["PYTHONPATH"] = os.pathsep.join(filter(None, [package_dir, env.get("PYTHONPATH")]))
+    proc = subprocess.Popen(
+        [sys.executable, "-c", script],
+        stdout=subprocess.PIPE,
+        stderr=subprocess.PIPE,
+        text=True,
+        env=env,
+    )
+    assert proc.stderr.readline() == "logged\n"
+    output, _ = proc.communicate(timeout=30)
+
+    written = output.count('msg="m"')
+    dropped = sum(int(n) for n in re.findall(r'msg="dropped (\d+) records', output))
+    assert dropped > 0
+    assert written + dropped == 150_000
+    assert "level=warn name=nexuslog" in output
+
+
+def test_exit_with_unopenable_file_does_not_hang() -> None:
+    import os
+    import subprocess
+    import sys
+
+    script = """
+import nexuslog as logging
+logging.basicConfig(filename="/proc/nope/x.log")
+logging.info("hi")
+logger = logging.Logger("x", "/proc/nope/y.log")
+logger.info("hi")
+logger.shutdown()
+"""
+    env = dict(os.environ)
+    package_dir = os.path.dirname(os.path.dirname(logging.__file__))
+    env["PYTHONPATH"] = os.pathsep.join(filter(None, [package_dir, env.get("PYTHONPATH")]))
+    proc = subprocess.run(
+        [sys.executable, "-c", script], capture_output=True, text=True, env=env, timeout=30
+    )
+
+    assert proc.returncode == 0
+    assert "error" in proc.stderr
+
+
+def test_max_latency_ships_idle_batch(tmp_path) -> None:
+    path = tmp_path / "nexuslog_test.log"
+    logging.basicConfig(filename=str(path), batch_size=1024, max_latency_ms=100)
+    logger = logging.getLogger("latency")
+    logger.info("first")
+    logger.info("second")
+
+    # the batch is far from full and nothing else is logged or flushed
+    contents = _read_logs(tmp_path)
+    assert "first" in contents and "second" in contents
+    logger.shutdown()
+
+
+def test_thread_exit_ships_buffered_records(tmp_path) -> None:
+    import threading
+
+    path = tmp_path / "nexuslog_test.log"
+    logging.basicConfig(filename=str(path), batch_size=1024)
+    logger = logging.getLogger("worker")
+
+    def work(i: int) -> None:
+        logger.info(f"last line from thread {i}")
+
+    threads = [threading.Thread(target=work, args=(i,)) for i in range(4)]
+    for t in threads:
+        t.start()
+    for t in threads:
+        t.join()
+
+    # no shutdown: the batches are sent as each thread exits
+    deadline = time.monotonic() + 3.0
+    while _read_logs(tmp_path).count("last line") < 4 and time.monotonic() < deadline:
+        time.sleep(0.05)
+    contents = _read_logs(tmp_path)
+    for i in range(4):
+        assert f"last line from thread {i}" in contents
+    logger.shutdown()
+
+
+def test_keyword_fields_keep_their_type(tmp_path) -> None:
+    import json
+
+    path = tmp_path / "nexuslog_test.log"
+    logging.basicConfig(filename=str(path), batch_size=1, fmt=logging.Format.Json)
+    logger = logging.getLogger("fields")
+    logger.info(
+        "order filled",
+        order_id=123,
+        px=101.5,
+        side="buy",
+        maker=False,
+        extra={"venue": "xnas", "tags": ["a", "b"]},
+    )
+    logger.shutdown()
+
+    record = json.loads(_read_logs(tmp_path).splitlines()[0])
+    assert record["msg"] == "order filled"
+    assert record["order_id"] == 123
+    assert record["px"] == 101.5
+    assert record["side"] == "buy"
+    assert record["maker"] is False
+    assert record["venue"] == "xnas"
+    assert record["tags"] == "['a', 'b']"
+
+
+def test_keyword_fields_in_logfmt(tmp_path) -> None:
+    path = tmp_path / "nexuslog_test.log"
+    logging.basicConfig(filename=str(path), batch_size=1)
+    logger = logging.getLogger("fields")
+    logger.warning("slow", took_ms=12, query="select 1")
+    logger.shutdown()
+
+    assert _read_logs(tmp_path).rstrip("\n").endswith(
+        'msg="slow" took_ms=12 query="select 1"'
+    )
+
+
+def test_bad_percent_args_are_reported_not_raised(tmp_path) -> None:
+    path = tmp_path / "nexuslog_test.log"
+    logging.basicConfig(filename=str(path), batch_size=1)
+    logger = logging.getLogger("args")
+    logger.info("%d items", "x")
+    logger.info("%s and %s", "one")
+    logger.shutdown()
+
+    lines = _read_logs(tmp_path).splitlines()
+    assert 'msg="%d items" format_error="TypeError: ' in lines[0]
+    assert 'msg="%s and %s" format_error="TypeError: ' in lines[1]
+
+
+def test_fields_cannot_overwrite_record_keys(tmp_path) -> None:
+    path = tmp_path / "nexuslog_test.log"
+    logging.basicConfig(filename=str(path), batch_size=1, fmt=logging.Format.Json)
+    logger = logging.getLogger("fields")
+    for kwargs in ({"msg": "x"}, {"extra": {"level": "x"}}, {"a": 1, "extra": {"a": 2}}):
+        try:
+            logger.info("clash", **kwargs)
+        except KeyError as err:
+            assert "Attempt to overwrite" in str(err)
+        else:
+            raise AssertionError(f"{kwargs} was accepted")
+    logger.info("kept", stacklevel=2)
+    logger.shutdown()
+
+    contents = _read_logs(tmp_path)
+    assert "clash" not in contents
+    assert contents.rstrip("\n").endswith('"msg":"kept"}')
+
+
+def test_logger_subclass_overrides_methods(tmp_path) -> None:
+    class Tagged(logging.Logger):
+        def info(self, message: object, *args: object, **kwargs: object) -> None:
+            super().info(f"[tagged] {message}", *args, **kwargs)
+
+    path = tmp_path / "nexuslog_test.log"
+    logging.basicConfig(filename=str(path), batch_size=1)
+    logger = Tagged("sub", str(path))
+    logger.info("hello %s", "there")
+    logger.shutdown()
+
+    assert logging.Logger.info.__doc__
+    assert 'msg="[tagged] hello there"' in _read_logs(tmp_path)
+
+
+def test_percent_args_are_formatted_only_when_enabled(tmp_path) -> None:
+    class Loud:
+        def __str__(self) -> str:
+            raise AssertionError("formatted a disabled record")
+
+    path = tmp_path / "nexuslog_test.log"
+    logging.basicConfig(filename=str(path), level=logging.INFO, batch_size=1)
+    logger = logging.getLogger("args")
+    logger.debug("skipped %s", Loud())
+    logger.info("x=%s y=%d", "a", 2)
+    logger.info("%(user)s logged in", {"user": "bob"})
+    logger.info("100%")
+    logger.shutdown()
+
+    contents = _read_logs(tmp_path)
+    assert 'msg="x=a y=2"' in contents
+    assert 'msg="bob logged in"' in contents
+    assert 'msg="100%"' in contents
+
+
+def test_exception_attaches_traceback(tmp_path) -> None:
+    import json
+
+    path = tmp_path / "nexuslog_test.log"
+    logging.basicConfig(filename=str(path), batch_size=1, fmt=logging.Format.Json)
+    logger = logging.getLogger("exc")
+    try:
+        1 / 0
+    except ZeroDivisionError as err:
+        logger.exception("division failed")
+        caught = err
+    logger.error("explicit", exc_info=caught)
+    logger.error("nothing raised", exc_info=True)
+    logger.info("where am I", stack_info=True)
+    logger.shutdown()
+
+    records = [json.loads(line) for line in _read_logs(tmp_path).splitlines()]
+    assert len(records) == 4
+    for record in records[:2]:
+        assert record["exc"].startswith("Traceback (most recent call last):")
+        assert record["exc"].endswith("ZeroDivisionError: division by zero")
+    assert "exc" not in records[2]
+    stack = records[3]["stack"]
+    assert stack.startswith("Stack (most recent call last):")
+    assert "test_exception_attaches_traceback" in stack
+    assert "nexuslog/__init__.py" not in stack
+
+
+def test_traceback_is_one_logfmt_record(tmp_path) -> None:
+    path = tmp_path / "nexuslog_test.log"
+    logging.basicConfig(filename=str(path), batch_size=1)
+    logger = logging.getLogger("exc")
+    try:
+        raise ValueError("bad value")
+    except ValueError:
+        logger.exception("failed")
+    logger.shutdown()
+
+    lines = _read_logs(tmp_path).splitlines()
+    assert len(lines) == 1
+    assert 'exc="Traceback (most recent call last):\\n' in lines[0]
+    assert lines[0].endswith('ValueError: bad value"')
+
+
+def test_critical_level_and_numeric_log(tmp_path) -> None:
+    path = tmp_path / "nexuslog_test.log"
+    logging.basicConfig(filename=str(path), level=logging.INFO, batch_size=1)
+    logger = logging.getLogger("levels")
+    logger.critical("halt")
+    logger.log(logging.CRITICAL, "enum critical")
+    logger.log(50, "numeric critical")
+    logger.log(40, "numeric error")
+    logger.log(30, "numeric warning")
+    logger.log(20, "numeric info")
+    logger.log(10, "numeric debug")
+    logger.shutdown()
+
+    contents = _read_logs(tmp_path)
+    assert 'level=critical name=levels msg="halt"' in contents
+    assert 'level=critical name=levels msg="enum critical"' in contents
+    assert 'level=critical name=levels msg="numeric critical"' in contents
+    assert 'level=error name=levels msg="numeric error"' in contents
+    assert 'level=warn name=levels msg="numeric warning"' in contents
+    assert 'level=info name=levels msg="numeric info"' in contents
+    assert "numeric debug" not in contents
+
+
+def test_critical_logger_level_drops_errors(tmp_path) -> None:
+    path = tmp_path / "nexuslog_test.log"
+    logging.basicConfig(filename=str(path), level=logging.CRITICAL, batch_size=1)
+    logger = logging.getLogger("levels")
+    logger.error("quiet")
+    logger.fatal("loud")
+    logger.shutdown()
+
+    contents = _read_logs(tmp_path)
+    assert "quiet" not in contents
+    assert 'level=critical name=levels msg="loud"' in contents
+
+
+def test_runtime_level_changes(tmp_path) -> None:
+    path = tmp_path / "nexuslog_test.log"
+    logging.basicConfig(filename=str(path), level=logging.INFO, batch_size=1)
+    first = logging.getLogger("svc")
+    second = logging.getLogger("svc")
+    other = logging.getLogger("other")
+
+    assert first.getEffectiveLevel() == logging.INFO
+    assert not first.isEnabledFor(logging.DEBUG)
+    assert first.isEnabledFor(20)
+
+    logging.setLevel("svc", logging.DEBUG)
+    assert first.isEnabledFor(10) and second.isEnabledFor(logging.DEBUG)
+    assert not other.isEnabledFor(logging.DEBUG)
+    assert logging.getLogger("svc").getEffectiveLevel() == logging.DEBUG
+    first.debug("debug on")
+    other.debug("other debug")
+
+    second.setLevel(40)
+    assert first.getEffectiveLevel() == logging.ERROR
+    second.warning("second warning")
+    other.warning("other warning")
+    first.shutdown()
+
+    contents = _read_logs(tmp_path)
+    assert "debug on" in contents
+    assert "other debug" not in contents
+    assert "second warning" not in contents
+    assert "other warning" in contents
+
+
+def test_dotted_loggers_inherit_levels(tmp_path) -> None:
+    path = tmp_path / "nexuslog_test.log"
+    logging.basicConfig(
+        filename=str(path),
+        level=logging.WARNING,
+        name_levels={"http.server": logging.ERROR},
+        batch_size=1,
+    )
+    http = logging.getLogger("http")
+    client = logging.getLogger("http.client")
+    server = logging.getLogger("http.server")
+    assert client._logger is logging.getLogger("http.client")._logger
+    assert client.getEffectiveLevel() == logging.WARNING
+
+    logging.setLevel("http", logging.DEBUG)
+    assert http.getEffectiveLevel() == logging.DEBUG
+    assert client.getEffectiveLevel() == logging.DEBUG
+    assert server.getEffectiveLevel() == logging.ERROR
+    assert logging.getLogger("http.client.pool").isEnabledFor(logging.DEBUG)
+    assert not logging.getLogger("httpx").isEnabledFor(logging.DEBUG)
+
+    client.debug("client debug")
+    server.warning("server warning")
+    logging.getLogger().setLevel(logging.ERROR)
+    assert client.getEffectiveLevel() == logging.DEBUG
+    client.shutdown()
+
+    contents = _read_logs(tmp_path)
+    assert "client debug" in contents
+    assert "server warning" not in contents
+
+
+def test_stdlib_records_are_captured(tmp_path) -> None:
+    path = tmp_path / "nexuslog_test.log"
+    logging.basicConfig(filename=str(path), batch_size=1)
+    handler = logging.capture_stdlib_logging(stdlib_logging.DEBUG)
+    try:
+        lib = stdlib_logging.getLogger("lib.client")
+        lib.debug("retrying %s in %d s", "GET", 2)
+        try:
+            raise ConnectionError("reset")
+        except ConnectionError:
+            lib.exception("request failed")
+        stdlib_logging.getLogger("lib").critical("giving up")
+        handler.flush()
+    finally:
+        stdlib_logging.getLogger().removeHandler(handler)
+        stdlib_logging.getLogger().setLevel(stdlib_logging.WARNING)
+
+    records = [_parse_logfmt(line) for line in _read_logs(tmp_path).splitlines()]
+    assert records[0]["name"] == "lib.client"
+    assert records[0]["level"] == "debug"
+    assert records[0]["msg"] == "retrying GET in 2 s"
+    assert records[1]["level"] == "error"
+    assert "ConnectionError: reset" in records[1]["exc"]
+    assert records[2]["level"] == "critical"
+
+
+def test_forward_rust_log_validates_directives(tmp_path) -> None:
+    logging.basicConfig(filename=str(tmp_path / "nexuslog_test.log"))
+    logging.forward_rust_log("info,hyper=warn")
+    logging.forward_rust_log(logging.DEBUG)
+    try:
+        logging.forward_rust_log("hyper=loud")
+    except ValueError as err:
+        assert "loud" in str(err)
+    else:
+        raise AssertionError("invalid directives accepted")
+
+
+def test_sinks_have_their_own_level_and_format(tmp_path) -> None:
+    path = tmp_path / "nexuslog_test.log"
+    json_path = tmp_path / "nexuslog_json.log"
+    logging.basicConfig(
+        filename=str(path),
+        level=logging.DEBUG,
+        batch_size=1,
+        sinks=[logging.Sink(str(json_path), level=logging.WARNING, fmt=logging.Format.Json)],
+    )
+    logger = logging.getLogger("svc")
+    logger.debug("debug record")
+    logger.error("error record", code=7)
+    logger.shutdown()
+
+    contents = _read_logs(tmp_path)
+    assert "debug record" in contents
+    assert "error record" in contents
+
+    import json
+
+    records = [json.loads(line) for line in _read_logs(tmp_path, "nexuslog_json").splitlines()]
+    assert [record["msg"] for record in records] == ["error record"]
+    assert records[0]["code"] == 7
+
+
+def test_stream_stderr_keeps_stdout_clean(tmp_path) -> None:
+    import os
+    import subprocess
+    import sys
+
+    script = """
+import sys
+import nexuslog as logging
+
+logging.basicConfig(stream=sys.stderr)
+logging.warning("to stderr")
+logger = logging.Logger("side", path=sys.stderr)
+logger.info("also stderr")
+logger.shutdown()
+print("program output")
+logging.shutdown()
+"""
+    env = dict(os.environ)
+    package_dir = os.path.dirname(os.path.dirname(logging.__file__))
+    env["PYTHONPATH"] = os.pathsep.join(filter(None, [package_dir, env.get("PYTHONPATH")]))
+    result = subprocess.run(
+        [sys.executable, "-c", script], capture_output=True, text=True, env=env, timeout=30
+    )
+
+    assert result.returncode == 0, result.stderr
+    assert result.stdout == "program output\n"
+    assert 'msg="to stderr"' in result.stderr
+    assert 'name=side msg="also stderr"' in result.stderr
+
+    try:
+        logging.basicConfig(filename=str(tmp_path / "x.log"), stream=sys.stderr)
+    except ValueError:
+        pass
+    else:
+        raise AssertionError("filename and stream accepted together")
@@ -1,10 +1,21 @@
-use chrono::{DateTime, Datelike, Local, Timelike};
-use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
-use log::{LevelFilter, Metadata, Record};
+use chrono::{
+    format::{Fixed, Item, Numeric, StrftimeItems},
+    DateTime, Datelike, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta,
+    Timelike,
+};
+use crossbeam_channel::{Receiver, RecvTimeoutError, SendTimeoutError, Sender, TrySendError};
+use log::{
+    kv::{Key, Source, Value, VisitSource, VisitValue},
+    LevelFilter, Metadata, Record,
+};
 use std::{
+    borrow::Cow,
     cell::RefCell,
     io::{BufWriter, Write},
-    sync::Arc,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex, Weak,
+    },
     thread::JoinHandle,
     time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
@@ -14,13 +25,21 @@ pub use log::{debug, error, info, trace, warn};
 pub type Level = LevelFilter;
 const CHANNEL_CAPACITY: usize = 65_536;
 const INLINE_MSG_CAP: usize = 256;
+const FIELD_KEY_CAP: usize = 24;
+const FIELD_VALUE_CAP: usize = 40;
 const DEFAULT_BATCH_SIZE: usize = 32;
+const OUTPUT_CAPACITY: usize = 1024 * 1024;
+const RETRY_DELAY_MIN: Duration = Duration::from_secs(1);
+const RETRY_DELAY_MAX: Duration = Duration::from_secs(60);
 
 thread_local! {
     static TS_CACHE: RefCell<ThreadTimestampCache> =
         RefCell::new(ThreadTimestampCache::new());
-    static ENTRY_BUFFER: RefCell<Vec<LogEntry>> =
-        RefCell::new(Vec::with_capacity(DEFAULT_BATCH_SIZE));
+    // batch for the last queue without a latency bound this thread logged to
+    static ENTRY_BUFFER: RefCell<Option<LocalBuffer>> =
+        const { RefCell::new(None) };
+    static ENTRY_BUFFERS: RefCell<Vec<ThreadBatch>> =
+        const { RefCell::new(Vec::new()) };
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -29,12 +48,71 @@ struct Timestamp {
     nanos: u32,
 }
 
+impl Timestamp {
+    fn duration_since(self, earlier: Timestamp) -> Duration {
+        Duration::new(self.secs, self.nanos).saturating_sub(Duration::new(earlier.secs, earlier.nanos))
+    }
+}
+
 #[derive(Debug)]
 struct LogEntry {
     ts: Timestamp,
     name: Option<Arc<str>>,
     level: log::Level,
+    // an error record logged as critical, which the `log` crate has no level for
+    critical: bool,
     msg: LogMessage,
+    location: Option<Location>,
+    fields: Fields,
+}
+
+impl LogEntry {
+    fn from_record(record: &Record, name: Option<Arc<str>>) -> Self {
+        let msg = {
+            let mut inline = ArrayString::<INLINE_MSG_CAP>::new();
+            use std::fmt::Write as _;
+            if write!(&mut inline, "{}", record.args()).is_ok() {
+                LogMessage::Inline(inline)
+            } else {
+                LogMessage::Heap(record.args().to_string())
+            }
+        };
+
+        LogEntry {
+            ts: cached_timestamp(),
+            name,
+            level: record.level(),
+            critical: false,
+            msg,
+            location: Some(Location::from_record(record)),
+            fields: Fields::from_kv(record.key_values()),
+        }
+    }
+}
+
+/// Where a record was logged, as reported by the `log` crate. Only a target set
+/// explicitly on the record is copied, the rest are static strings.
+#[derive(Debug)]
+struct Location {
+    target: Cow<'static, str>,
+    module_path: Option<&'static str>,
+    file: Option<&'static str>,
+    line: Option<u32>,
+}
+
+impl Location {
+    fn from_record(record: &Record) -> Self {
+        let target = match record.module_path_static() {
+            Some(module) if module == record.target() => Cow::Borrowed(module),
+            _ => Cow::Owned(record.target().to_string()),
+        };
+        Location {
+            target,
+            module_path: record.module_path_static(),
+            file: record.file_static(),
+            line: record.line(),
+        }
+    }
 }
 
 impl LogEntry {
@@ -54,9 +132,18 @@ impl LogEntry {
     pub fn msg(&self) -> &str {
         self.msg.as_str()
     }
+    #[inline]
+    fn location(&self) -> Option<&Location> {
+        self.location.as_ref()
+    }
+    #[inline]
+    fn fields(&self) -> &Fields {
+        &self.fields
+    }
 }
 
 #[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
 enum LogMessage {
     Inline(ArrayString<INLINE_MSG_CAP>),
     Heap(String),
@@ -72,6 +159,158 @@ impl LogMessage {
     }
 }
 
+/// Text kept inline up to `N` bytes, spilling to the heap beyond that.
+#[derive(Debug)]
+enum InlineStr<const N: usize> {
+    Inline(ArrayString<N>),
+    Heap(String),
+}
+
+impl<const N: usize> InlineStr<N> {
+    fn new(value: &str) -> Self {
+        match ArrayString::from(value) {
+            Ok(inline) => InlineStr::Inline(inline),
+            Err(_) => InlineStr::Heap(value.to_owned()),
+        }
+    }
+
+    fn display(value: &dyn std::fmt::Display) -> Self {
+        use std::fmt::Write as _;
+        let mut inline = ArrayString::new();
+        if write!(&mut inline, "{}", value).is_ok() {
+            InlineStr::Inline(inline)
+        } else {
+            InlineStr::Heap(value.to_string())
+        }
+    }
+
+    #[inline]
+    fn as_str(&self) -> &str {
+        match self {
+            InlineStr::Inline(value) => value.as_str(),
+            InlineStr::Heap(value) => value.as_str(),
+        }
+    }
+}
+
+/// A key/value pair attached to a record.
+#[derive(Debug)]
+struct Field {
+    key: InlineStr<FIELD_KEY_CAP>,
+    value: FieldValue,
+}
+
+/// Field values keep their type so JSON output can write numbers and booleans
+/// unquoted. Anything else is rendered to a string when captured.
+#[derive(Debug)]
+enum FieldValue {
+    Str(InlineStr<FIELD_VALUE_CAP>),
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    Bool(bool),
+}
+
+impl FieldValue {
+    fn from_kv(value: &Value) -> Self {
+        struct Capture(Option<FieldValue>);
+
+        impl<'v> VisitValue<'v> for Capture {
+            fn visit_any(&mut self, value: Value) -> Result<(), log::kv::Error> {
+                self.0 = Some(FieldValue::Str(InlineStr::display(&value)));
+                Ok(())
+            }
+            fn visit_i64(&mut self, value: i64) -> Result<(), log::kv::Error> {
+                self.0 = Some(FieldValue::I64(value));
+                Ok(())
+            }
+            fn visit_u64(&mut self, value: u64) -> Result<(), log::kv::Error> {
+                self.0 = Some(FieldValue::U64(value));
+                Ok(())
+            }
+            fn visit_f64(&mut self, value: f64) -> Result<(), log::kv::Error> {
+                self.0 = Some(FieldValue::F64(value));
+                Ok(())
+            }
+            fn visit_bool(&mut self, value: bool) -> Result<(), log::kv::Error> {
+                self.0 = Some(FieldValue::Bool(value));
+                Ok(())
+            }
+            fn visit_str(&mut self, value: &str) -> Result<(), log::kv::Error> {
+                self.0 = Some(FieldValue::Str(InlineStr::new(value)));
+                Ok(())
+            }
+        }
+
+        let mut capture = Capture(None);
+        let _ = value.visit(&mut capture);
+        capture
+            .0
+            .unwrap_or_else(|| FieldValue::Str(InlineStr::display(value)))
+    }
+}
+
+/// Keys the record writes itself, which fields must not repeat.
+const RECORD_KEYS: [&str; 9] = [
+    "time",
+    "level",
+    "name",
+    "target",
+    "loc",
+    "msg",
+    "format_error",
+    "exc",
+    "stack",
+];
+
+/// Key/value pairs of a record. Most records have none, so they live on the
+/// heap and cost the entry a single empty `Vec`.
+#[derive(Debug, Default)]
+struct Fields(Vec<Field>);
+
+impl Fields {
+    fn from_kv(source: &dyn Source) -> Self {
+        struct Collect(Fields);
+
+        impl<'kvs> VisitSource<'kvs> for Collect {
+            fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), log::kv::Error> {
+                // moved aside, so JSON parsers keeping the last member still
+                // see the record's own `msg` or `level`
+                let key = match key.as_str() {
+                    key if RECORD_KEYS.contains(&key) => InlineStr::display(&format_args!("fields.{key}")),
+                    key => InlineStr::new(key),
+                };
+                self.0.push(Field {
+                    key,
+                    value: FieldValue::from_kv(&value),
+                });
+                Ok(())
+            }
+        }
+
+        let mut collect = Collect(Fields::default());
+        let _ = source.visit(&mut collect);
+        collect.0
+    }
+
+    fn push(&mut self, field: Field) {
+        self.0.push(field);
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &Field> {
+        self.0.iter()
+    }
+
+    /// Appends a string field already rendered on the heap, e.g. a traceback.
+    #[cfg(feature = "python")]
+    fn push_str(&mut self, key: &str, value: String) {
+        self.push(Field {
+            key: InlineStr::new(key),
+            value: FieldValue::Str(InlineStr::Heap(value)),
+        });
+    }
+}
+
 struct ThreadTimestampCache {
     base_instant: Instant,
     base_secs: u64,
@@ -110,257 +349,1480 @@ impl ThreadTimestampCache {
         }
     }
 }
-enum Action {
-    WriteBatch(Vec<LogEntry>),
-    Flush,
-    Exit,
+
+/// Output layout of a single log record.
+#[derive(Debug, Clone, Default)]
+pub enum Format {
+    /// `time=... level=... name=... msg="..."`
+    #[default]
+    Logfmt,
+    /// One JSON object per line: `{"time":...,"level":...,"name":...,"msg":...}`
+    Json,
+    /// User-defined line template, see [`Pattern`].
+    Pattern(Pattern),
 }
 
-#[derive(Debug)]
-struct Context<P: ToString + Send> {
-    rx: Receiver<Action>,
-    path: Option<P>,
-    date: chrono::NaiveDate,
-    unix_ts: bool,
+/// A line template such as `{time:%H:%M:%S%.6f} [{level:>5}] {name}: {msg}`,
+/// compiled once into segments and rendered by the worker thread.
+///
+/// Fields are `{time}`, `{level}`, `{name}` and `{msg}`, and for records logged
+/// through the `log` crate `{target}`, `{module}` and `{loc}` (`file:line`),
+/// which are empty otherwise. `{time:...}` takes a
+/// chrono strftime spec, the other fields take an optional alignment and width
+/// (`{level:>5}`, `{name:<12}`, `{msg:^40}`). `{{` and `}}` are literal braces.
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    segments: Vec<Segment>,
+    slots: usize,
 }
 
-pub struct Handle {
-    tx: Sender<Action>,
-    thread: Option<JoinHandle<()>>,
+#[derive(Debug, Clone)]
+enum Segment {
+    Literal(String),
+    Time(Option<TimeFormat>),
+    Level(Pad),
+    Name(Pad),
+    Msg(Pad),
+    Target(Pad),
+    Module(Pad),
+    Loc(Pad),
 }
 
-impl Handle {
-    pub fn stop(&mut self) {
-        if let Some(thread) = self.thread.take() {
-            let _ = self.tx.send(Action::Exit);
-            let _ = thread.join();
-        }
-    }
+#[derive(Debug, Clone)]
+struct TimeFormat {
+    items: Vec<Item<'static>>,
+    subsec: bool,
+    // index into `TimestampCache::pattern_times` when rendered once per second
+    slot: usize,
 }
-impl Drop for Handle {
-    fn drop(&mut self) {
-        self.stop();
-    }
+
+#[derive(Debug, Clone, Copy, Default)]
+enum Align {
+    #[default]
+    Left,
+    Right,
+    Center,
 }
 
-struct Logger {
-    tx: Sender<Action>,
-    name: Option<Arc<str>>,
+#[derive(Debug, Clone, Copy, Default)]
+struct Pad {
+    align: Align,
+    width: usize,
 }
 
-impl log::Log for Logger {
-    fn enabled(&self, metadata: &Metadata) -> bool {
-        metadata.level() <= log::max_level()
-    }
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternError(String);
 
-    fn log(&self, record: &Record) {
-        if !self.enabled(record.metadata()) {
-            return;
-        }
+impl std::fmt::Display for PatternError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid pattern: {}", self.0)
+    }
+}
 
-        let msg = {
-            let mut inline = ArrayString::<INLINE_MSG_CAP>::new();
-            use std::fmt::Write as _;
-            if write!(&mut inline, "{}", record.args()).is_ok() {
-                LogMessage::Inline(inline)
-            } else {
-                LogMessage::Heap(record.args().to_string())
+impl std::error::Error for PatternError {}
+
+impl Pattern {
+    pub fn parse(pattern: &str) -> Result<Self, PatternError> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut slots = 0;
+        let mut rest = pattern;
+        while let Some(pos) = rest.find(['{', '}']) {
+            literal.push_str(&rest[..pos]);
+            let tail = &rest[pos..];
+            if tail.starts_with("{{") || tail.starts_with("}}") {
+                literal.push_str(&tail[..1]);
+                rest = &tail[2..];
+                continue;
             }
-        };
+            if tail.starts_with('}') {
+                return Err(PatternError("unmatched `}`".to_string()));
+            }
+            let end = tail
+                .find('}')
+                .ok_or_else(|| PatternError("unclosed `{`".to_string()))?;
+            let (field, spec) = match tail[1..end].split_once(':') {
+                Some((field, spec)) => (field, Some(spec)),
+                None => (&tail[1..end], None),
+            };
+            if !literal.is_empty() {
+                segments.push(Segment::Literal(std::mem::take(&mut literal)));
+            }
+            segments.push(match field {
+                "time" => Segment::Time(match spec {
+                    Some(spec) => Some(TimeFormat::parse(spec, &mut slots)?),
+                    None => None,
+                }),
+                "level" => Segment::Level(Pad::parse(field, spec)?),
+                "name" => Segment::Name(Pad::parse(field, spec)?),
+                "msg" => Segment::Msg(Pad::parse(field, spec)?),
+                "target" => Segment::Target(Pad::parse(field, spec)?),
+                "module" => Segment::Module(Pad::parse(field, spec)?),
+                "loc" => Segment::Loc(Pad::parse(field, spec)?),
+                _ => return Err(PatternError(format!("unknown field `{field}`"))),
+            });
+            rest = &tail[end + 1..];
+        }
+        literal.push_str(rest);
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+        Ok(Pattern { segments, slots })
+    }
+}
 
-        let entry = LogEntry {
-            ts: cached_timestamp(),
-            name: self.name.as_ref().map(Arc::clone),
-            level: record.level(),
-            msg,
-        };
+impl std::str::FromStr for Pattern {
+    type Err = PatternError;
 
-        push_entry(&self.tx, entry);
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Pattern::parse(s)
     }
+}
 
-    fn flush(&self) {
-        flush_thread_buffer(&self.tx);
-        let _ = self.tx.send(Action::Flush);
+impl TimeFormat {
+    fn parse(spec: &str, slots: &mut usize) -> Result<Self, PatternError> {
+        let items = StrftimeItems::new(spec)
+            .parse_to_owned()
+            .map_err(|_| PatternError(format!("invalid time format `{spec}`")))?;
+        let subsec = items.iter().any(|item| {
+            matches!(
+                item,
+                Item::Fixed(
+                    Fixed::Nanosecond
+                        | Fixed::Nanosecond3
+                        | Fixed::Nanosecond6
+                        | Fixed::Nanosecond9
+                        | Fixed::RFC3339
+                        | Fixed::Internal(_)
+                ) | Item::Numeric(Numeric::Nanosecond, _)
+            )
+        });
+        let slot = *slots;
+        if !subsec {
+            *slots += 1;
+        }
+        Ok(TimeFormat { items, subsec, slot })
     }
 }
 
-fn open_file(path: &str) -> Result<std::fs::File, std::io::Error> {
-    let dir = std::path::Path::new(path);
-    if let Some(parent) = dir.parent() {
-        std::fs::create_dir_all(parent)?;
+impl Pad {
+    fn parse(field: &str, spec: Option<&str>) -> Result<Self, PatternError> {
+        let Some(spec) = spec else {
+            return Ok(Pad::default());
+        };
+        let (align, width) = match spec.as_bytes().first() {
+            Some(b'<') => (Align::Left, &spec[1..]),
+            Some(b'>') => (Align::Right, &spec[1..]),
+            Some(b'^') => (Align::Center, &spec[1..]),
+            _ => (Align::Left, spec),
+        };
+        let width = width
+            .parse()
+            .map_err(|_| PatternError(format!("invalid spec `{spec}` for `{field}`")))?;
+        Ok(Pad { align, width })
     }
+}
 
-    std::fs::OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(path)
+enum Action {
+    WriteBatch(Vec<LogEntry>),
+    /// Report drops and flush, then answer on the channel.
+    Flush(Sender<()>),
+    Exit,
 }
 
-fn rotate<P: ToString + Send>(
-    ctx: &Context<P>,
-) -> Result<BufWriter<Box<dyn Write>>, std::io::Error> {
-    let capacity = 1024 * 1024;
-    match &ctx.path {
-        Some(path) => {
-            let path = {
-                let postfix = ctx.date.format("_%Y%m%d").to_string();
-                let path_str = path.to_string();
-                let input = std::path::Path::new(&path_str);
-                let stem = input.file_stem().and_then(|s| s.to_str());
-                let ext = input.extension().and_then(|s| s.to_str());
-                if let (Some(stem), Some(ext)) = (stem, ext) {
-                    let filename = format!("{stem}{postfix}.{ext}");
-                    match input.parent() {
-                        Some(parent) if !parent.as_os_str().is_empty() => {
-                            parent.join(filename).to_string_lossy().to_string()
-                        }
-                        _ => filename,
-                    }
-                } else {
-                    format!("{}{}.log", path_str, postfix)
-                }
-            };
-            let file = open_file(&path)?;
-            Ok(BufWriter::with_capacity(capacity, Box::new(file)))
-        }
-        None => {
-            let target = Box::new(std::io::stdout());
-            Ok(BufWriter::with_capacity(capacity, target))
+/// What a logging call does when the worker's queue is full.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Overflow {
+    /// Wait for the worker to catch up.
+    #[default]
+    Block,
+    /// Discard the batch being sent.
+    DropNewest,
+    /// Discard the oldest queued batch to make room.
+    DropOldest,
+}
+
+/// Records one thread has buffered for one queue, shared with the worker
+/// when the queue has a latency bound.
+type LocalBatch = Mutex<Vec<LogEntry>>;
+
+/// A thread's batch for a queue without a latency bound, only ever touched by
+/// that thread. Sent when the thread exits or moves on to another queue.
+struct LocalBuffer {
+    queue: Queue,
+    pending: Vec<LogEntry>,
+}
+
+impl Drop for LocalBuffer {
+    fn drop(&mut self) {
+        if !self.pending.is_empty() {
+            self.queue.send_batch(std::mem::take(&mut self.pending));
         }
     }
 }
 
-fn now_timestamp() -> Timestamp {
-    let now = SystemTime::now();
-    let since_epoch = now
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_else(|err| err.duration());
-    Timestamp {
-        secs: since_epoch.as_secs(),
-        nanos: since_epoch.subsec_nanos(),
+/// A thread's batch together with the queue it belongs to, so whatever is
+/// still buffered is sent when the thread exits.
+struct ThreadBatch {
+    queue: Queue,
+    batch: Arc<LocalBatch>,
+}
+
+impl Drop for ThreadBatch {
+    fn drop(&mut self) {
+        let pending = std::mem::take(&mut *self.batch.lock().unwrap());
+        if !pending.is_empty() {
+            self.queue.send_batch(pending);
+        }
     }
 }
 
-fn cached_timestamp() -> Timestamp {
-    TS_CACHE.with(|cache| cache.borrow_mut().now())
+/// Sending half of a worker's queue, applying the overflow policy to batches.
+#[derive(Debug, Clone)]
+struct Queue {
+    tx: Sender<Action>,
+    // only kept for `Overflow::DropOldest`, to evict from the front
+    rx: Option<Receiver<Action>>,
+    overflow: Overflow,
+    state: Arc<QueueState>,
 }
 
-struct TimestampCache {
-    last_secs: u64,
-    year: i32,
-    month: u32,
-    day: u32,
-    hour: u32,
-    minute: u32,
-    second: u32,
-    offset_sign: char,
-    offset_h: i32,
-    offset_m: i32,
-    date: chrono::NaiveDate,
-    time_prefix: String,
-    offset_prefix: String,
-    unix_prefix: String,
+#[derive(Debug)]
+struct QueueState {
+    // records discarded by the overflow policy, reported by the worker
+    dropped: AtomicU64,
+    max_latency: Option<Duration>,
+    // set once the worker is gone, so threads can let go of their batch
+    closed: AtomicBool,
+    // every thread's batch for this queue, so the worker can ship idle ones
+    batches: Mutex<Vec<Weak<LocalBatch>>>,
 }
 
-impl TimestampCache {
-    fn new() -> Self {
-        Self {
-            last_secs: u64::MAX,
-            year: 0,
-            month: 0,
-            day: 0,
-            hour: 0,
-            minute: 0,
-            second: 0,
-            offset_sign: '+',
-            offset_h: 0,
-            offset_m: 0,
-            date: chrono::NaiveDate::from_ymd_opt(1970, 1, 1).unwrap(),
-            time_prefix: String::new(),
-            offset_prefix: String::new(),
-            unix_prefix: String::new(),
-        }
+impl Queue {
+    fn new(
+        capacity: usize,
+        overflow: Overflow,
+        max_latency: Option<Duration>,
+    ) -> (Self, Receiver<Action>) {
+        let (tx, rx) = crossbeam_channel::bounded(capacity);
+        let queue = Queue {
+            tx,
+            rx: (overflow == Overflow::DropOldest).then(|| rx.clone()),
+            overflow,
+            state: Arc::new(QueueState {
+                dropped: AtomicU64::new(0),
+                max_latency,
+                closed: AtomicBool::new(false),
+                batches: Mutex::new(Vec::new()),
+            }),
+        };
+        (queue, rx)
     }
 
-    fn update(&mut self, secs: u64) {
-        if self.last_secs == secs {
-            return;
+    /// Sends the batches threads have left sitting for at least `age`. Batches
+    /// being written to are skipped and picked up next time.
+    fn ship_idle(&self, age: Duration) {
+        let now = now_timestamp();
+        let batches = self.state.batches.lock().unwrap();
+        for batch in batches.iter().filter_map(Weak::upgrade) {
+            let Ok(mut pending) = batch.try_lock() else {
+                continue;
+            };
+            match pending.first() {
+                Some(first) if now.duration_since(first.ts) >= age => {}
+                _ => continue,
+            }
+            // sent under the lock so it is queued ahead of the thread's next batch
+            let entries = std::mem::take(&mut *pending);
+            if let Err(TrySendError::Full(Action::WriteBatch(entries))) =
+                self.tx.try_send(Action::WriteBatch(entries))
+            {
+                *pending = entries;
+                return;
+            }
         }
+    }
 
-        let dt: DateTime<Local> = DateTime::from(UNIX_EPOCH + Duration::from_secs(secs));
-        self.last_secs = secs;
-        self.year = dt.year();
-        self.month = dt.month();
-        self.day = dt.day();
-        self.hour = dt.hour();
-        self.minute = dt.minute();
-        self.second = dt.second();
-        self.date = dt.date_naive();
-
-        let offset = dt.offset().local_minus_utc();
-        self.offset_sign = if offset >= 0 { '+' } else { '-' };
-        let offset_abs = offset.abs();
-        self.offset_h = offset_abs / 3600;
-        self.offset_m = (offset_abs % 3600) / 60;
-
-        self.time_prefix = format!(
-            "time={:04}-{:02}-{:02}T{:02}:{:02}:{:02}.",
-            self.year, self.month, self.day, self.hour, self.minute, self.second
-        );
-        self.offset_prefix =
-            format!("{}{:02}:{:02} level=", self.offset_sign, self.offset_h, self.offset_m);
-        self.unix_prefix = format!("time={}.", secs);
+    /// Sends a control action, waiting for room even when the policy drops
+    /// records.
+    fn send(&self, action: Action) {
+        let _ = self.tx.send(action);
     }
-}
 
-fn worker<P: ToString + Send>(mut ctx: Context<P>) -> Result<(), std::io::Error> {
-    let timeout = Duration::from_secs(1);
+    /// Waits until the worker wrote and flushed everything queued so far and
+    /// reported the records dropped until then. Gives up once the worker is
+    /// gone, e.g. when its file could not be opened.
+    fn flush(&self) {
+        const POLL: Duration = Duration::from_millis(100);
 
-    let mut target = rotate(&ctx)?;
-    let mut last_flush = Instant::now();
-    let mut cache = TimestampCache::new();
-    loop {
-        match ctx.rx.recv_timeout(timeout) {
-            Ok(Action::WriteBatch(entries)) => {
-                for entry in entries {
-                    write_entry(&mut target, &mut ctx, &mut cache, entry)?;
-                }
+        let (tx, rx) = crossbeam_channel::bounded(1);
+        let mut action = Action::Flush(tx);
+        loop {
+            if self.state.closed.load(Ordering::Relaxed) {
+                return;
             }
-            Ok(Action::Flush) => {
-                target.flush()?;
+            match self.tx.send_timeout(action, POLL) {
+                Ok(()) => break,
+                Err(SendTimeoutError::Timeout(rejected)) => action = rejected,
+                Err(SendTimeoutError::Disconnected(_)) => return,
             }
-            Ok(Action::Exit) => {
-                target.flush()?;
-                break;
+        }
+        loop {
+            match rx.recv_timeout(POLL) {
+                Err(RecvTimeoutError::Timeout) if !self.state.closed.load(Ordering::Relaxed) => {}
+                _ => return,
+            }
+        }
+    }
+
+    fn send_batch(&self, batch: Vec<LogEntry>) {
+        let mut action = Action::WriteBatch(batch);
+        match self.overflow {
+            Overflow::Block => {
+                let _ = self.tx.send(action);
+            }
+            Overflow::DropNewest => {
+                if let Err(TrySendError::Full(Action::WriteBatch(batch))) = self.tx.try_send(action) {
+                    self.state.dropped.fetch_add(batch.len() as u64, Ordering::Relaxed);
+                }
+            }
+            Overflow::DropOldest => loop {
+                match self.tx.try_send(action) {
+                    Ok(()) | Err(TrySendError::Disconnected(_)) => return,
+                    Err(TrySendError::Full(rejected)) => action = rejected,
+                }
+                let Some(rx) = &self.rx else {
+                    return;
+                };
+                match rx.try_recv() {
+                    Ok(Action::WriteBatch(evicted)) => {
+                        self.state.dropped.fetch_add(evicted.len() as u64, Ordering::Relaxed);
+                    }
+                    // only records are dropped, control actions go back in line
+                    Ok(control @ (Action::Exit | Action::Flush(_))) => self.send(control),
+                    Err(_) => {}
+                }
+            },
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Context {
+    rx: Receiver<Action>,
+    queue: Queue,
+}
+
+impl Drop for Context {
+    fn drop(&mut self) {
+        self.queue.state.closed.store(true, Ordering::Relaxed);
+        // unanswered flushes see their answer channel go away
+        while self.rx.try_recv().is_ok() {}
+    }
+}
+
+/// One output of a logger with a level and layout of its own. Besides the
+/// output set up on [`NexusLogBuilder`] itself, a logger writes to every sink
+/// added with [`NexusLogBuilder::sink`], all from the same worker thread.
+///
+/// ```no_run
+/// use nexuslog::{Format, Level, NexusLogBuilder, Sink};
+///
+/// let _handle = NexusLogBuilder::new("myapp")
+///     .path("/var/log/app.log")
+///     .level(Level::Debug)
+///     .sink(Sink::stdout().level(Level::Warn))
+///     .sink(Sink::file("/var/log/app.json").level(Level::Info).format(Format::Json))
+///     .build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Sink {
+    target: Target,
+    // on top of the logger's directives, which every record passes first
+    level: LevelFilter,
+    format: Format,
+    unix_ts: bool,
+    escape: bool,
+    // write `target=` and `loc=` for records that carry them
+    location: bool,
+    rotation: Rotation,
+    max_bytes: Option<u64>,
+    retention: Retention,
+    compression: Option<Compression>,
+}
+
+/// Where a sink writes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Target {
+    Stdout,
+    Stderr,
+    File(String),
+}
+
+impl std::fmt::Display for Target {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Target::Stdout => f.write_str("stdout"),
+            Target::Stderr => f.write_str("stderr"),
+            Target::File(path) => f.write_str(path),
+        }
+    }
+}
+
+impl Sink {
+    /// Writes every record to stdout.
+    pub fn stdout() -> Self {
+        Sink {
+            target: Target::Stdout,
+            level: LevelFilter::Trace,
+            format: Format::Logfmt,
+            unix_ts: false,
+            escape: true,
+            location: false,
+            rotation: Rotation::Daily,
+            max_bytes: None,
+            retention: Retention::default(),
+            compression: None,
+        }
+    }
+
+    /// Writes every record to stderr.
+    pub fn stderr() -> Self {
+        Sink {
+            target: Target::Stderr,
+            ..Sink::stdout()
+        }
+    }
+
+    /// Writes every record to `path`, with a postfix per rotation period.
+    pub fn file(path: impl ToString) -> Self {
+        Sink {
+            target: Target::File(path.to_string()),
+            ..Sink::stdout()
+        }
+    }
+
+    fn path(&self) -> Option<&str> {
+        match &self.target {
+            Target::File(path) => Some(path),
+            Target::Stdout | Target::Stderr => None,
+        }
+    }
+
+    /// Most verbose level written to this sink.
+    pub fn level(mut self, level: Level) -> Self {
+        self.level = level;
+        self
+    }
+
+    pub fn format(mut self, format: Format) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// See [`NexusLogBuilder::unix_ts`].
+    pub fn unix_ts(mut self, unix_ts: bool) -> Self {
+        self.unix_ts = unix_ts;
+        self
+    }
+
+    /// See [`NexusLogBuilder::escape`].
+    pub fn escape(mut self, escape: bool) -> Self {
+        self.escape = escape;
+        self
+    }
+
+    /// See [`NexusLogBuilder::location`].
+    pub fn location(mut self, location: bool) -> Self {
+        self.location = location;
+        self
+    }
+
+    pub fn rotation(mut self, rotation: Rotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// See [`NexusLogBuilder::max_bytes`].
+    pub fn max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    pub fn retention(mut self, retention: Retention) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    pub fn compression(mut self, compression: Compression) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+}
+
+/// A sink as the worker thread sees it, with its rotation period and the
+/// file currently open.
+struct ActiveSink {
+    sink: Sink,
+    // start of the current rotation period and of the next one, local time
+    period: NaiveDateTime,
+    period_end: NaiveDateTime,
+    output: Output,
+    // per sink, patterns cache their rendered times
+    cache: TimestampCache,
+    // after an I/O error the sink is skipped until `retry_at` and then
+    // reopened, the delay doubling while the errors keep coming
+    retry_at: Option<Instant>,
+    retry_delay: Duration,
+}
+
+impl ActiveSink {
+    fn open(sink: Sink) -> Result<Self, std::io::Error> {
+        let (period, period_end) = sink.rotation.current();
+        let output = rotate(&sink, period)?;
+        Ok(ActiveSink {
+            sink,
+            period,
+            period_end,
+            output,
+            cache: TimestampCache::new(),
+            retry_at: None,
+            retry_delay: RETRY_DELAY_MIN,
+        })
+    }
+
+    /// Reports `err` and skips this sink for a while, the others keep going.
+    fn fail(&mut self, err: std::io::Error) {
+        eprintln!(
+            "error writing to {}: {}, retrying in {}s",
+            self.sink.target,
+            err,
+            self.retry_delay.as_secs()
+        );
+        self.retry_at = Some(Instant::now() + self.retry_delay);
+        self.retry_delay = (self.retry_delay * 2).min(RETRY_DELAY_MAX);
+    }
+
+    /// Whether the sink can be written, reopening it in the current period
+    /// once the delay after a failure has passed.
+    fn ready(&mut self, compressor: Option<&Compressor>) -> bool {
+        match self.retry_at {
+            None => return true,
+            Some(at) if Instant::now() < at => return false,
+            Some(_) => {}
+        }
+        let (period, period_end) = self.sink.rotation.current();
+        match rotate(&self.sink, period) {
+            Ok(output) => {
+                self.period = period;
+                self.period_end = period_end;
+                self.retry_at = None;
+                let retired = std::mem::replace(&mut self.output, output).path;
+                after_rotate(self, retired, compressor);
+                true
+            }
+            Err(err) => {
+                self.fail(err);
+                false
+            }
+        }
+    }
+}
+
+/// Compression applied to a file once the worker has moved on to the next one.
+/// Each variant is behind the cargo feature of the same name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    #[cfg(feature = "gzip")]
+    Gzip,
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+impl Compression {
+    fn extension(self) -> &'static str {
+        match self {
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => "gz",
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => "zst",
+        }
+    }
+
+    #[cfg_attr(not(any(feature = "gzip", feature = "zstd")), allow(unused_variables))]
+    fn encode(
+        self,
+        input: &mut std::fs::File,
+        output: std::fs::File,
+    ) -> Result<(), std::io::Error> {
+        match self {
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => {
+                let mut encoder = flate2::write::GzEncoder::new(
+                    BufWriter::new(output),
+                    flate2::Compression::default(),
+                );
+                std::io::copy(input, &mut encoder)?;
+                let output = encoder.finish()?.into_inner().map_err(|e| e.into_error())?;
+                output.sync_all()
+            }
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => {
+                let mut encoder = zstd::Encoder::new(BufWriter::new(output), 0)?;
+                std::io::copy(input, &mut encoder)?;
+                let output = encoder.finish()?.into_inner().map_err(|e| e.into_error())?;
+                output.sync_all()
+            }
+        }
+    }
+}
+
+/// Extensions of every compressed variant a rotated file can have on disk.
+const COMPRESSED_EXTENSIONS: [&str; 2] = ["gz", "zst"];
+
+/// Compresses retired files on a thread of its own so the worker never waits
+/// on it. On Linux the thread runs at a lower priority; elsewhere it keeps the
+/// default one. Pending files are finished before the worker exits.
+struct Compressor {
+    tx: Option<Sender<(String, Compression)>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Compressor {
+    fn spawn() -> Result<Self, std::io::Error> {
+        let (tx, rx) = crossbeam_channel::unbounded::<(String, Compression)>();
+        let thread = std::thread::Builder::new()
+            .name("nexuslog-compress".to_string())
+            .spawn(move || {
+                lower_thread_priority();
+                for (path, compression) in rx {
+                    if let Err(err) = compress_file(&path, compression) {
+                        eprintln!("error compressing {}: {}", path, err);
+                    }
+                }
+            })?;
+        Ok(Compressor {
+            tx: Some(tx),
+            thread: Some(thread),
+        })
+    }
+
+    fn submit(&self, path: String, compression: Compression) {
+        if let Some(tx) = &self.tx {
+            let _ = tx.send((path, compression));
+        }
+    }
+}
+
+/// Raises the nice value of the calling thread, so compression yields to the
+/// application and the worker.
+#[cfg(target_os = "linux")]
+fn lower_thread_priority() {
+    // SAFETY: plain syscall without pointers; on Linux `who == 0` with
+    // `PRIO_PROCESS` is the calling thread, not the whole process
+    unsafe {
+        libc::setpriority(libc::PRIO_PROCESS, 0, 10);
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn lower_thread_priority() {}
+
+impl Drop for Compressor {
+    fn drop(&mut self) {
+        self.tx.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Writes `{path}.{ext}` through a temporary file and links it into place, so
+/// the compressed file either appears complete or not at all. An existing
+/// compressed file is never replaced, the plain file is then kept instead. The
+/// plain file is only removed once the compressed one is in place.
+fn compress_file(path: &str, compression: Compression) -> Result<(), std::io::Error> {
+    let target = format!("{}.{}", path, compression.extension());
+    let tmp = format!("{}.tmp", target);
+    let result = std::fs::File::open(path).and_then(|mut input| {
+        let output = std::fs::File::create(&tmp)?;
+        compression.encode(&mut input, output)?;
+        // unlike a rename, fails if `target` exists
+        std::fs::hard_link(&tmp, &target)
+    });
+    let _ = std::fs::remove_file(&tmp);
+    result?;
+    std::fs::remove_file(path)
+}
+
+/// Limits on the rotated files kept on disk, enforced by the worker after each
+/// rotation. Only files following the logger's own naming pattern are touched.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Retention {
+    /// Number of files to keep, including the one being written.
+    pub max_files: Option<usize>,
+    /// Delete files whose period started more than this many days before today.
+    /// Ignored with [`Rotation::Never`].
+    pub max_age_days: Option<u32>,
+    /// Cap on the combined size of all files, oldest are deleted first.
+    pub max_total_bytes: Option<u64>,
+}
+
+impl Retention {
+    fn is_empty(&self) -> bool {
+        self.max_files.is_none() && self.max_age_days.is_none() && self.max_total_bytes.is_none()
+    }
+}
+
+/// The worker's current output, counting bytes for size-based rotation.
+struct Output {
+    writer: BufWriter<Box<dyn Write + Send>>,
+    path: Option<String>,
+    written: u64,
+    index: u32,
+}
+
+impl Output {
+    fn console(target: Box<dyn Write + Send>) -> Self {
+        Output {
+            writer: BufWriter::with_capacity(OUTPUT_CAPACITY, target),
+            path: None,
+            written: 0,
+            index: 0,
+        }
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, std::io::Error> {
+        let n = self.writer.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), std::io::Error> {
+        self.writer.write_all(buf)?;
+        self.written += buf.len() as u64;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), std::io::Error> {
+        self.writer.flush()
+    }
+}
+
+/// Per-target levels in the `env_logger` syntax, e.g.
+/// `info,hyper=warn,mycrate::db=trace`.
+///
+/// A bare level sets the default, `target=level` applies to that target and its
+/// submodules, and a bare target enables everything for it. The most specific
+/// target wins.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Directives {
+    default: LevelFilter,
+    // sorted longest first, so the first match is the most specific one
+    targets: Vec<(String, LevelFilter)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectiveError(String);
+
+impl std::fmt::Display for DirectiveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid log directive: {}", self.0)
+    }
+}
+
+impl std::error::Error for DirectiveError {}
+
+impl Directives {
+    /// A single level for every target.
+    pub fn new(default: LevelFilter) -> Self {
+        Directives {
+            default,
+            targets: Vec::new(),
+        }
+    }
+
+    /// Parses `spec`. Targets without a directive fall back to `Info` unless the
+    /// spec has a bare level.
+    pub fn parse(spec: &str) -> Result<Self, DirectiveError> {
+        Directives::parse_with_default(spec, LevelFilter::Info)
+    }
+
+    /// Reads `NEXUSLOG`, falling back to `RUST_LOG`. Targets without a
+    /// directive use `default` unless the variable has a bare level.
+    pub fn from_env(default: LevelFilter) -> Result<Self, DirectiveError> {
+        match std::env::var("NEXUSLOG").or_else(|_| std::env::var("RUST_LOG")) {
+            Ok(spec) => Directives::parse_with_default(&spec, default),
+            Err(_) => Ok(Directives::new(default)),
+        }
+    }
+
+    fn parse_with_default(spec: &str, default: LevelFilter) -> Result<Self, DirectiveError> {
+        let mut directives = Directives::new(default);
+        for item in spec.split(',').map(str::trim).filter(|item| !item.is_empty()) {
+            let (target, level) = match item.split_once('=') {
+                Some((target, level)) => (Some(target.trim()), level.trim()),
+                None => match item.parse::<LevelFilter>() {
+                    Ok(_) => (None, item),
+                    Err(_) => (Some(item), "trace"),
+                },
+            };
+            let level = level
+                .parse::<LevelFilter>()
+                .map_err(|_| DirectiveError(format!("unknown level `{level}` in `{item}`")))?;
+            match target {
+                Some("") => return Err(DirectiveError(format!("missing target in `{item}`"))),
+                Some(target) => {
+                    directives.targets.retain(|(t, _)| t != target);
+                    directives.targets.push((target.to_string(), level));
+                }
+                None => directives.default = level,
+            }
+        }
+        directives
+            .targets
+            .sort_by_key(|(target, _)| std::cmp::Reverse(target.len()));
+        Ok(directives)
+    }
+
+    /// Level enabled for `target`.
+    #[inline]
+    fn level(&self, target: &str) -> LevelFilter {
+        for (prefix, level) in &self.targets {
+            if let Some(rest) = target.strip_prefix(prefix.as_str()) {
+                if rest.is_empty() || rest.starts_with("::") {
+                    return *level;
+                }
+            }
+        }
+        self.default
+    }
+
+    /// Most verbose level of any directive, for [`log::set_max_level`].
+    fn max_level(&self) -> LevelFilter {
+        self.targets
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.default, Ord::max)
+    }
+}
+
+impl std::str::FromStr for Directives {
+    type Err = DirectiveError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Directives::parse(s)
+    }
+}
+
+pub struct Handle {
+    tx: Sender<Action>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Handle {
+    pub fn stop(&mut self) {
+        if let Some(thread) = self.thread.take() {
+            let _ = self.tx.send(Action::Exit);
+            let _ = thread.join();
+        }
+    }
+}
+impl Drop for Handle {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+struct Logger {
+    queue: Queue,
+    name: Option<Arc<str>>,
+    batch_size: usize,
+    directives: Directives,
+}
+
+impl log::Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.directives.level(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let entry = LogEntry::from_record(record, self.name.as_ref().map(Arc::clone));
+        push_entry(&self.queue, entry, self.batch_size);
+    }
+
+    fn flush(&self) {
+        flush_thread_buffer(&self.queue);
+        self.queue.flush();
+    }
+}
+
+fn open_file(path: &str) -> Result<std::fs::File, std::io::Error> {
+    let dir = std::path::Path::new(path);
+    if let Some(parent) = dir.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+}
+
+/// How often the worker starts a new file. The file name carries a postfix
+/// matching the granularity.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Rotation {
+    /// A single `{stem}.{ext}` file.
+    Never,
+    /// Every N minutes counted from local midnight, `{stem}_%Y%m%d_%H%M.{ext}`.
+    Minutes(u32),
+    /// `{stem}_%Y%m%d_%H.{ext}`
+    Hourly,
+    /// `{stem}_%Y%m%d.{ext}`
+    #[default]
+    Daily,
+    /// ISO weeks starting on Monday, `{stem}_%GW%V.{ext}`.
+    Weekly,
+}
+
+impl Rotation {
+    /// Start of the period the local time `now` falls in.
+    fn period(self, now: NaiveDateTime) -> NaiveDateTime {
+        let midnight = now.date().and_time(NaiveTime::MIN);
+        match self {
+            Rotation::Never => NaiveDateTime::MIN,
+            Rotation::Minutes(n) => {
+                let n = n.clamp(1, 24 * 60);
+                let minutes = (now.hour() * 60 + now.minute()) / n * n;
+                midnight + TimeDelta::minutes(minutes as i64)
+            }
+            Rotation::Hourly => midnight + TimeDelta::hours(now.hour() as i64),
+            Rotation::Daily => midnight,
+            Rotation::Weekly => {
+                midnight - TimeDelta::days(now.weekday().num_days_from_monday() as i64)
+            }
+        }
+    }
+
+    /// Bounds of the period the current local time falls in.
+    fn current(self) -> (NaiveDateTime, NaiveDateTime) {
+        let period = self.period(Local::now().naive_local());
+        (period, self.next(period))
+    }
+
+    /// Start of the period following the one that starts at `period`.
+    fn next(self, period: NaiveDateTime) -> NaiveDateTime {
+        match self {
+            Rotation::Never => NaiveDateTime::MAX,
+            Rotation::Minutes(n) => {
+                let next_day = period.date().and_time(NaiveTime::MIN) + TimeDelta::days(1);
+                (period + TimeDelta::minutes(n.clamp(1, 24 * 60) as i64)).min(next_day)
+            }
+            Rotation::Hourly => period + TimeDelta::hours(1),
+            Rotation::Daily => period + TimeDelta::days(1),
+            Rotation::Weekly => period + TimeDelta::days(7),
+        }
+    }
+
+    fn postfix(self, period: NaiveDateTime) -> String {
+        match self {
+            Rotation::Never => String::new(),
+            Rotation::Minutes(_) => period.format("_%Y%m%d_%H%M").to_string(),
+            Rotation::Hourly => period.format("_%Y%m%d_%H").to_string(),
+            Rotation::Daily => period.format("_%Y%m%d").to_string(),
+            Rotation::Weekly => period.format("_%GW%V").to_string(),
+        }
+    }
+
+    /// Splits the postfix written by [`Rotation::postfix`] off the start of
+    /// `name`, returning its period and the remainder.
+    fn parse_postfix(self, name: &str) -> Option<(NaiveDateTime, &str)> {
+        fn number(s: Option<&str>) -> Option<u32> {
+            let s = s?;
+            if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            s.parse().ok()
+        }
+
+        if self == Rotation::Never {
+            return Some((NaiveDateTime::MIN, name));
+        }
+        let name = name.strip_prefix('_')?;
+        if self == Rotation::Weekly {
+            let year = number(name.get(..4))?;
+            let week = number(name.get(5..7))?;
+            if name.get(4..5)? != "W" {
+                return None;
+            }
+            let date = NaiveDate::from_isoywd_opt(year as i32, week, chrono::Weekday::Mon)?;
+            return Some((date.and_time(NaiveTime::MIN), name.get(7..)?));
+        }
+
+        let date = NaiveDate::from_ymd_opt(
+            number(name.get(..4))? as i32,
+            number(name.get(4..6))?,
+            number(name.get(6..8))?,
+        )?;
+        let (hour, minute, len) = match self {
+            Rotation::Minutes(_) => (number(name.get(9..11))?, number(name.get(11..13))?, 13),
+            Rotation::Hourly => (number(name.get(9..11))?, 0, 11),
+            _ => (0, 0, 8),
+        };
+        if len > 8 && name.get(8..9)? != "_" {
+            return None;
+        }
+        let time = NaiveTime::from_hms_opt(hour, minute, 0)?;
+        Some((date.and_time(time), name.get(len..)?))
+    }
+}
+
+/// Naming scheme of the files rotated from one configured path:
+/// `{stem}{postfix}.{ext}` for the first file of a period,
+/// `{stem}{postfix}.{index}.{ext}` for the ones that follow once `max_bytes`
+/// is reached.
+struct RotatedName {
+    dir: Option<std::path::PathBuf>,
+    stem: String,
+    ext: String,
+    rotation: Rotation,
+}
+
+impl RotatedName {
+    fn new(path: &str, rotation: Rotation) -> Self {
+        let input = std::path::Path::new(path);
+        let dir = input
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .map(|parent| parent.to_path_buf());
+        let stem = input.file_stem().and_then(|s| s.to_str());
+        let ext = input.extension().and_then(|s| s.to_str());
+        match (stem, ext) {
+            (Some(stem), Some(ext)) => RotatedName {
+                dir,
+                stem: stem.to_string(),
+                ext: ext.to_string(),
+                rotation,
+            },
+            _ => RotatedName {
+                dir,
+                stem: input
+                    .file_name()
+                    .map(|s| s.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+                ext: "log".to_string(),
+                rotation,
+            },
+        }
+    }
+
+    fn path(&self, period: NaiveDateTime, index: u32) -> String {
+        let postfix = self.rotation.postfix(period);
+        let filename = if index > 0 {
+            format!("{}{postfix}.{index}.{}", self.stem, self.ext)
+        } else {
+            format!("{}{postfix}.{}", self.stem, self.ext)
+        };
+        match &self.dir {
+            Some(dir) => dir.join(filename).to_string_lossy().to_string(),
+            None => filename,
+        }
+    }
+
+    /// Period and index of a file produced by this scheme, plain or compressed,
+    /// `None` for anything else.
+    fn parse(&self, filename: &str) -> Option<(NaiveDateTime, u32)> {
+        let filename = COMPRESSED_EXTENSIONS
+            .iter()
+            .find_map(|ext| filename.strip_suffix(ext)?.strip_suffix('.'))
+            .unwrap_or(filename);
+        let rest = filename.strip_prefix(self.stem.as_str())?;
+        let (period, rest) = self.rotation.parse_postfix(rest)?;
+        let rest = rest.strip_prefix('.')?;
+        if rest == self.ext {
+            return Some((period, 0));
+        }
+        let (index, ext) = rest.split_once('.')?;
+        if ext != self.ext || !index.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        Some((period, index.parse().ok()?))
+    }
+}
+
+/// Whether `path` exists as a plain or compressed file.
+fn rotated_exists(path: &str) -> bool {
+    std::path::Path::new(path).exists()
+        || COMPRESSED_EXTENSIONS
+            .iter()
+            .any(|ext| std::path::Path::new(&format!("{path}.{ext}")).exists())
+}
+
+/// Deletes rotated files that fall outside `retention`, newest first are kept.
+/// The file currently being written is never deleted.
+fn enforce_retention(
+    name: &RotatedName,
+    retention: &Retention,
+    active_period: NaiveDateTime,
+    active_index: u32,
+) -> Result<(), std::io::Error> {
+    let dir = name
+        .dir
+        .clone()
+        .unwrap_or_else(|| std::path::PathBuf::from("."));
+    let today = Local::now().date_naive();
+
+    let mut active_len = 0;
+    let mut files = Vec::new();
+    for dir_entry in std::fs::read_dir(&dir)? {
+        let dir_entry = dir_entry?;
+        let filename = dir_entry.file_name();
+        let Some((period, index)) = filename.to_str().and_then(|f| name.parse(f)) else {
+            continue;
+        };
+        let metadata = dir_entry.metadata()?;
+        if !metadata.is_file() {
+            continue;
+        }
+        if period == active_period && index == active_index {
+            active_len = metadata.len();
+            continue;
+        }
+        files.push((period, index, dir_entry.path(), metadata.len()));
+    }
+    files.sort_by_key(|file| std::cmp::Reverse((file.0, file.1)));
+
+    let mut kept = 1;
+    let mut total = active_len;
+    for (period, _, file, len) in files {
+        // without rotation the file names carry no date to age by
+        let expired = name.rotation != Rotation::Never
+            && retention
+                .max_age_days
+                .is_some_and(|days| (today - period.date()).num_days() > days as i64);
+        let too_many = retention.max_files.is_some_and(|n| kept >= n);
+        let too_large = retention
+            .max_total_bytes
+            .is_some_and(|cap| total + len > cap);
+        if expired || too_many || too_large {
+            std::fs::remove_file(&file)?;
+        } else {
+            kept += 1;
+            total += len;
+        }
+    }
+    Ok(())
+}
+
+/// Hands the file the sink just left to the compressor and applies the
+/// retention policy.
+fn after_rotate(active: &ActiveSink, retired: Option<String>, compressor: Option<&Compressor>) {
+    let sink = &active.sink;
+    if let (Some(compressor), Some(compression), Some(retired), Some(path)) =
+        (compressor, sink.compression, retired, sink.path())
+    {
+        // only files before the current one, which the sink never opens again
+        let name = RotatedName::new(path, sink.rotation);
+        let position = std::path::Path::new(&retired)
+            .file_name()
+            .and_then(|file| name.parse(&file.to_string_lossy()));
+        if position.is_some_and(|position| position < (active.period, active.output.index)) {
+            compressor.submit(retired, compression);
+        }
+    }
+    if sink.retention.is_empty() {
+        return;
+    }
+    if let Some(path) = sink.path() {
+        let name = RotatedName::new(path, sink.rotation);
+        if let Err(err) =
+            enforce_retention(&name, &sink.retention, active.period, active.output.index)
+        {
+            eprintln!("error {}", err);
+        }
+    }
+}
+
+fn open_output(
+    name: &RotatedName,
+    period: NaiveDateTime,
+    index: u32,
+) -> Result<Output, std::io::Error> {
+    let path = name.path(period, index);
+    let file = open_file(&path)?;
+    let written = file.metadata()?.len();
+    Ok(Output {
+        writer: BufWriter::with_capacity(OUTPUT_CAPACITY, Box::new(file)),
+        path: Some(path),
+        written,
+        index,
+    })
+}
+
+fn rotate(sink: &Sink, period: NaiveDateTime) -> Result<Output, std::io::Error> {
+    match &sink.target {
+        Target::File(path) => {
+            let name = RotatedName::new(path, sink.rotation);
+            let Some(max_bytes) = sink.max_bytes else {
+                return open_output(&name, period, 0);
+            };
+
+            // resume after a restart at the last file of the period
+            let mut index = 0;
+            while rotated_exists(&name.path(period, index + 1)) {
+                index += 1;
+            }
+            let current = name.path(period, index);
+            if !std::path::Path::new(&current).exists() && rotated_exists(&current) {
+                // already compressed, never append to it again
+                return open_output(&name, period, index + 1);
+            }
+            let output = open_output(&name, period, index)?;
+            if output.written >= max_bytes {
+                open_output(&name, period, index + 1)
+            } else {
+                Ok(output)
+            }
+        }
+        Target::Stdout => Ok(Output::console(Box::new(std::io::stdout()))),
+        Target::Stderr => Ok(Output::console(Box::new(std::io::stderr()))),
+    }
+}
+
+fn now_timestamp() -> Timestamp {
+    let now = SystemTime::now();
+    let since_epoch = now
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_else(|err| err.duration());
+    Timestamp {
+        secs: since_epoch.as_secs(),
+        nanos: since_epoch.subsec_nanos(),
+    }
+}
+
+fn cached_timestamp() -> Timestamp {
+    TS_CACHE.with(|cache| cache.borrow_mut().now())
+}
+
+struct TimestampCache {
+    last_secs: u64,
+    year: i32,
+    month: u32,
+    day: u32,
+    hour: u32,
+    minute: u32,
+    second: u32,
+    offset_sign: char,
+    offset_h: i32,
+    offset_m: i32,
+    offset: FixedOffset,
+    local: NaiveDateTime,
+    datetime_prefix: String,
+    offset_suffix: String,
+    pattern_secs: u64,
+    pattern_times: Vec<String>,
+    time_prefix: String,
+    offset_prefix: String,
+    unix_prefix: String,
+    json_time_prefix: String,
+    json_offset_prefix: String,
+    json_unix_prefix: String,
+}
+
+impl TimestampCache {
+    fn new() -> Self {
+        Self {
+            last_secs: u64::MAX,
+            year: 0,
+            month: 0,
+            day: 0,
+            hour: 0,
+            minute: 0,
+            second: 0,
+            offset_sign: '+',
+            offset_h: 0,
+            offset_m: 0,
+            offset: FixedOffset::east_opt(0).unwrap(),
+            local: DateTime::UNIX_EPOCH.naive_utc(),
+            datetime_prefix: String::new(),
+            offset_suffix: String::new(),
+            pattern_secs: u64::MAX,
+            pattern_times: Vec::new(),
+            time_prefix: String::new(),
+            offset_prefix: String::new(),
+            unix_prefix: String::new(),
+            json_time_prefix: String::new(),
+            json_offset_prefix: String::new(),
+            json_unix_prefix: String::new(),
+        }
+    }
+
+    fn update(&mut self, secs: u64) {
+        if self.last_secs == secs {
+            return;
+        }
+
+        let dt: DateTime<Local> = DateTime::from(UNIX_EPOCH + Duration::from_secs(secs));
+        self.last_secs = secs;
+        self.year = dt.year();
+        self.month = dt.month();
+        self.day = dt.day();
+        self.hour = dt.hour();
+        self.minute = dt.minute();
+        self.second = dt.second();
+        self.local = dt.naive_local();
+
+        let offset = dt.offset().local_minus_utc();
+        self.offset_sign = if offset >= 0 { '+' } else { '-' };
+        let offset_abs = offset.abs();
+        self.offset_h = offset_abs / 3600;
+        self.offset_m = (offset_abs % 3600) / 60;
+        self.offset = *dt.offset();
+
+        self.datetime_prefix = format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        );
+        self.offset_suffix = format!("{}{:02}:{:02}", self.offset_sign, self.offset_h, self.offset_m);
+
+        self.time_prefix = format!(
+            "time={:04}-{:02}-{:02}T{:02}:{:02}:{:02}.",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        );
+        self.offset_prefix =
+            format!("{}{:02}:{:02} level=", self.offset_sign, self.offset_h, self.offset_m);
+        self.unix_prefix = format!("time={}.", secs);
+
+        self.json_time_prefix = format!(
+            "{{\"time\":\"{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        );
+        self.json_offset_prefix = format!(
+            "{}{:02}:{:02}\",\"level\":\"",
+            self.offset_sign, self.offset_h, self.offset_m
+        );
+        self.json_unix_prefix = format!("{{\"time\":{}.", secs);
+    }
+
+    fn datetime(&self, ts: Timestamp) -> DateTime<FixedOffset> {
+        DateTime::from_timestamp(ts.secs as i64, ts.nanos)
+            .unwrap_or_default()
+            .with_timezone(&self.offset)
+    }
+}
+
+/// Opens the first output of every sink and starts the compression thread,
+/// before the worker is spawned so that failures reach the caller.
+fn open_sinks(sinks: Vec<Sink>) -> Result<(Vec<ActiveSink>, Option<Compressor>), InitError> {
+    let sinks = sinks
+        .into_iter()
+        .map(ActiveSink::open)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(InitError::OpenFile)?;
+    let compressor = if sinks.iter().any(|active| active.sink.compression.is_some()) {
+        Some(Compressor::spawn().map_err(InitError::SpawnThread)?)
+    } else {
+        None
+    };
+    Ok((sinks, compressor))
+}
+
+fn worker(
+    ctx: Context,
+    mut sinks: Vec<ActiveSink>,
+    compressor: Option<Compressor>,
+) {
+    // idle batches are shipped once they are half the allowed latency old
+    let idle_age = ctx
+        .queue
+        .state
+        .max_latency
+        .map(|latency| (latency / 2).max(Duration::from_millis(1)));
+    let timeout = idle_age.map_or(Duration::from_secs(1), |age| age.min(Duration::from_secs(1)));
+
+    for active in &sinks {
+        after_rotate(active, None, compressor.as_ref());
+    }
+    let mut last_flush = Instant::now();
+    let mut last_idle_check = Instant::now();
+    loop {
+        match ctx.rx.recv_timeout(timeout) {
+            Ok(Action::WriteBatch(entries)) => {
+                for entry in &entries {
+                    write_entry(&mut sinks, compressor.as_ref(), entry);
+                }
+            }
+            Ok(Action::Flush(done)) => {
+                report_dropped(&mut sinks, &ctx, compressor.as_ref());
+                flush_sinks(&mut sinks);
+                let _ = done.send(());
+            }
+            Ok(Action::Exit) => break,
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+
+        if let Some(age) = idle_age {
+            if last_idle_check.elapsed() >= age {
+                last_idle_check = Instant::now();
+                ctx.queue.ship_idle(age);
             }
-            Err(RecvTimeoutError::Timeout) => {}
-            Err(RecvTimeoutError::Disconnected) => break,
         }
 
         if last_flush.elapsed() >= Duration::from_secs(1) {
             last_flush = Instant::now();
-            target.flush()?;
+            report_dropped(&mut sinks, &ctx, compressor.as_ref());
+            flush_sinks(&mut sinks);
         }
     }
 
-    Ok(())
+    report_dropped(&mut sinks, &ctx, compressor.as_ref());
+    flush_sinks(&mut sinks);
 }
 
-fn write_entry<P: ToString + Send>(
-    target: &mut BufWriter<Box<dyn Write>>,
-    ctx: &mut Context<P>,
-    cache: &mut TimestampCache,
-    entry: LogEntry,
-) -> Result<(), std::io::Error> {
-    let ts = entry.ts();
-    cache.update(ts.secs);
+fn flush_sinks(sinks: &mut [ActiveSink]) {
+    for active in sinks.iter_mut().filter(|active| active.retry_at.is_none()) {
+        match active.output.flush() {
+            Ok(()) => active.retry_delay = RETRY_DELAY_MIN,
+            Err(err) => active.fail(err),
+        }
+    }
+}
 
-    if cache.date != ctx.date {
-        ctx.date = cache.date;
-        *target = rotate(ctx)?;
+/// Writes a warning with the number of records discarded by the overflow
+/// policy since the last report.
+fn report_dropped(
+    sinks: &mut [ActiveSink],
+    ctx: &Context,
+    compressor: Option<&Compressor>,
+) {
+    let dropped = ctx.queue.state.dropped.swap(0, Ordering::Relaxed);
+    if dropped == 0 {
+        return;
     }
+    let entry = LogEntry {
+        ts: now_timestamp(),
+        name: Some(Arc::from("nexuslog")),
+        level: log::Level::Warn,
+        critical: false,
+        msg: LogMessage::Heap(format!("dropped {dropped} records, queue full")),
+        location: None,
+        fields: Fields::default(),
+    };
+    write_entry(sinks, compressor, &entry)
+}
 
+/// Writes `entry` to every working sink whose level it passes.
+fn write_entry(sinks: &mut [ActiveSink], compressor: Option<&Compressor>, entry: &LogEntry) {
     let level = match entry.level() {
+        log::Level::Error if entry.critical => "critical",
         log::Level::Trace => "trace",
         log::Level::Debug => "debug",
         log::Level::Info => "info",
@@ -368,7 +1830,71 @@ fn write_entry<P: ToString + Send>(
         log::Level::Error => "error",
     };
 
-    if ctx.unix_ts {
+    for active in sinks.iter_mut() {
+        if entry.level() <= active.sink.level && active.ready(compressor) {
+            if let Err(err) = write_sink_entry(active, compressor, entry, level) {
+                active.fail(err);
+            }
+        }
+    }
+}
+
+fn write_sink_entry(
+    active: &mut ActiveSink,
+    compressor: Option<&Compressor>,
+    entry: &LogEntry,
+    level: &str,
+) -> Result<(), std::io::Error> {
+    let ts = entry.ts();
+    active.cache.update(ts.secs);
+
+    // only forward: late records, e.g. from a batch that waited, go to the
+    // current file instead of reopening an older one
+    if active.cache.local >= active.period_end {
+        active.period = active.sink.rotation.period(active.cache.local);
+        active.period_end = active.sink.rotation.next(active.period);
+        // replaced only once the next file is open, so a failure keeps the
+        // current one and its path for compression later
+        let output = rotate(&active.sink, active.period)?;
+        let retired = std::mem::replace(&mut active.output, output).path;
+        after_rotate(active, retired, compressor);
+    } else if let (Some(max_bytes), Some(path)) = (active.sink.max_bytes, active.sink.path()) {
+        if active.output.written >= max_bytes {
+            let name = RotatedName::new(path, active.sink.rotation);
+            let output = open_output(&name, active.period, active.output.index + 1)?;
+            let retired = std::mem::replace(&mut active.output, output).path;
+            after_rotate(active, retired, compressor);
+        }
+    }
+
+    let ActiveSink {
+        sink,
+        output: target,
+        cache,
+        ..
+    } = active;
+    match &sink.format {
+        Format::Logfmt => {
+            write_logfmt(target, sink.unix_ts, sink.escape, sink.location, cache, entry, level)
+        }
+        Format::Json => write_json(target, sink.unix_ts, sink.location, cache, entry, level),
+        Format::Pattern(pattern) => {
+            write_pattern(target, pattern, sink.unix_ts, sink.escape, cache, entry, level)
+        }
+    }
+}
+
+fn write_logfmt(
+    target: &mut Output,
+    unix_ts: bool,
+    escape: bool,
+    location: bool,
+    cache: &TimestampCache,
+    entry: &LogEntry,
+    level: &str,
+) -> Result<(), std::io::Error> {
+    let ts = entry.ts();
+    if unix_ts {
         target.write_all(cache.unix_prefix.as_bytes())?;
         write!(target, "{:09} level={}", ts.nanos, level)?;
     } else {
@@ -378,66 +1904,660 @@ fn write_entry<P: ToString + Send>(
         target.write_all(level.as_bytes())?;
     }
 
+    let location = entry.location().filter(|_| location);
+    if escape {
+        if let Some(name) = entry.name() {
+            target.write_all(b" name=")?;
+            write_logfmt_value(target, name)?;
+        }
+        if let Some(location) = location {
+            target.write_all(b" target=")?;
+            write_logfmt_value(target, &location.target)?;
+            if let Some(file) = location.file {
+                target.write_all(b" loc=")?;
+                if logfmt_needs_quotes(file) {
+                    target.write_all(b"\"")?;
+                    write_escaped(target, file, true)?;
+                    write_line(target, location.line)?;
+                    target.write_all(b"\"")?;
+                } else {
+                    target.write_all(file.as_bytes())?;
+                    write_line(target, location.line)?;
+                }
+            }
+        }
+        target.write_all(b" msg=")?;
+        write_quoted(target, entry.msg())?;
+        for field in entry.fields().iter() {
+            target.write_all(b" ")?;
+            write_logfmt_value(target, field.key.as_str())?;
+            target.write_all(b"=")?;
+            match &field.value {
+                FieldValue::Str(value) => write_logfmt_value(target, value.as_str())?,
+                value => write_field_value(target, value)?,
+            }
+        }
+        target.write_all(b"\n")?;
+    } else {
+        if let Some(name) = entry.name() {
+            target.write_all(b" name=")?;
+            target.write_all(name.as_bytes())?;
+        }
+        if let Some(location) = location {
+            target.write_all(b" target=")?;
+            target.write_all(location.target.as_bytes())?;
+            if let Some(file) = location.file {
+                target.write_all(b" loc=")?;
+                target.write_all(file.as_bytes())?;
+                write_line(target, location.line)?;
+            }
+        }
+        target.write_all(b" msg=\"")?;
+        target.write_all(entry.msg().as_bytes())?;
+        target.write_all(b"\"")?;
+        for field in entry.fields().iter() {
+            target.write_all(b" ")?;
+            target.write_all(field.key.as_str().as_bytes())?;
+            target.write_all(b"=")?;
+            match &field.value {
+                FieldValue::Str(value) => target.write_all(value.as_str().as_bytes())?,
+                value => write_field_value(target, value)?,
+            }
+        }
+        target.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+fn write_json(
+    target: &mut Output,
+    unix_ts: bool,
+    location: bool,
+    cache: &TimestampCache,
+    entry: &LogEntry,
+    level: &str,
+) -> Result<(), std::io::Error> {
+    let ts = entry.ts();
+    if unix_ts {
+        target.write_all(cache.json_unix_prefix.as_bytes())?;
+        write!(target, "{:09},\"level\":\"{}", ts.nanos, level)?;
+    } else {
+        target.write_all(cache.json_time_prefix.as_bytes())?;
+        write!(target, "{:06}", ts.nanos / 1_000)?;
+        target.write_all(cache.json_offset_prefix.as_bytes())?;
+        target.write_all(level.as_bytes())?;
+    }
+    target.write_all(b"\"")?;
+
     if let Some(name) = entry.name() {
-        target.write_all(b" name=")?;
-        target.write_all(name.as_bytes())?;
+        target.write_all(b",\"name\":")?;
+        write_quoted(target, name)?;
+    }
+    if let Some(location) = entry.location().filter(|_| location) {
+        target.write_all(b",\"target\":")?;
+        write_quoted(target, &location.target)?;
+        if let Some(file) = location.file {
+            target.write_all(b",\"loc\":\"")?;
+            write_escaped(target, file, true)?;
+            write_line(target, location.line)?;
+            target.write_all(b"\"")?;
+        }
+    }
+    target.write_all(b",\"msg\":")?;
+    write_quoted(target, entry.msg())?;
+    for field in entry.fields().iter() {
+        target.write_all(b",")?;
+        write_quoted(target, field.key.as_str())?;
+        target.write_all(b":")?;
+        match &field.value {
+            FieldValue::Str(value) => write_quoted(target, value.as_str())?,
+            // JSON has no NaN or infinity
+            FieldValue::F64(value) if !value.is_finite() => target.write_all(b"null")?,
+            value => write_field_value(target, value)?,
+        }
+    }
+    target.write_all(b"}\n")?;
+    Ok(())
+}
+
+fn write_pattern(
+    target: &mut Output,
+    pattern: &Pattern,
+    unix_ts: bool,
+    escape: bool,
+    cache: &mut TimestampCache,
+    entry: &LogEntry,
+    level: &str,
+) -> Result<(), std::io::Error> {
+    use std::fmt::Write as _;
+
+    let ts = entry.ts();
+    if cache.pattern_secs != ts.secs || cache.pattern_times.len() < pattern.slots {
+        cache.pattern_secs = ts.secs;
+        cache.pattern_times.resize(pattern.slots, String::new());
+        let dt = cache.datetime(ts);
+        for segment in &pattern.segments {
+            if let Segment::Time(Some(format)) = segment {
+                if !format.subsec {
+                    let rendered = &mut cache.pattern_times[format.slot];
+                    rendered.clear();
+                    let _ = write!(rendered, "{}", dt.format_with_items(format.items.iter()));
+                }
+            }
+        }
+    }
+
+    for segment in &pattern.segments {
+        match segment {
+            Segment::Literal(text) => target.write_all(text.as_bytes())?,
+            Segment::Time(None) if unix_ts => write!(target, "{}.{:09}", ts.secs, ts.nanos)?,
+            Segment::Time(None) => {
+                target.write_all(cache.datetime_prefix.as_bytes())?;
+                write!(target, "{:06}", ts.nanos / 1_000)?;
+                target.write_all(cache.offset_suffix.as_bytes())?;
+            }
+            Segment::Time(Some(format)) if format.subsec => {
+                let dt = cache.datetime(ts);
+                write!(target, "{}", dt.format_with_items(format.items.iter()))?;
+            }
+            Segment::Time(Some(format)) => {
+                target.write_all(cache.pattern_times[format.slot].as_bytes())?;
+            }
+            Segment::Level(pad) => write_padded(target, *pad, level.as_bytes())?,
+            Segment::Name(pad) => write_field(target, *pad, entry.name().unwrap_or(""), escape)?,
+            Segment::Msg(pad) => write_field(target, *pad, entry.msg(), escape)?,
+            Segment::Target(pad) => {
+                let value = entry.location().map_or("", |location| &location.target);
+                write_field(target, *pad, value, escape)?
+            }
+            Segment::Module(pad) => {
+                let value = entry.location().and_then(|location| location.module_path);
+                write_field(target, *pad, value.unwrap_or(""), escape)?
+            }
+            Segment::Loc(pad) => match entry.location() {
+                Some(Location {
+                    file: Some(file),
+                    line,
+                    ..
+                }) if pad.width == 0 => {
+                    write_field(target, *pad, file, escape)?;
+                    write_line(target, *line)?;
+                }
+                Some(Location {
+                    file: Some(file),
+                    line,
+                    ..
+                }) => {
+                    let loc = match line {
+                        Some(line) => format!("{file}:{line}"),
+                        None => file.to_string(),
+                    };
+                    write_field(target, *pad, &loc, escape)?;
+                }
+                _ => write_padded(target, *pad, b"")?,
+            },
+        }
+    }
+    target.write_all(b"\n")
+}
+
+fn write_field<W: Write>(
+    target: &mut W,
+    pad: Pad,
+    value: &str,
+    escape: bool,
+) -> Result<(), std::io::Error> {
+    if pad.width == 0 {
+        return if escape {
+            write_escaped(target, value, false)
+        } else {
+            target.write_all(value.as_bytes())
+        };
+    }
+
+    if escape {
+        let mut buf = Vec::with_capacity(value.len());
+        write_escaped(&mut buf, value, false)?;
+        write_padded(target, pad, &buf)
+    } else {
+        write_padded(target, pad, value.as_bytes())
+    }
+}
+
+fn write_padded<W: Write>(target: &mut W, pad: Pad, value: &[u8]) -> Result<(), std::io::Error> {
+    const SPACES: &[u8; 64] = &[b' '; 64];
+
+    fn spaces<W: Write>(target: &mut W, mut n: usize) -> Result<(), std::io::Error> {
+        while n > 0 {
+            let chunk = n.min(SPACES.len());
+            target.write_all(&SPACES[..chunk])?;
+            n -= chunk;
+        }
+        Ok(())
+    }
+
+    let chars = value.iter().filter(|&&b| (b & 0xc0) != 0x80).count();
+    let fill = pad.width.saturating_sub(chars);
+    let (before, after) = match pad.align {
+        Align::Left => (0, fill),
+        Align::Right => (fill, 0),
+        Align::Center => (fill / 2, fill - fill / 2),
+    };
+    spaces(target, before)?;
+    target.write_all(value)?;
+    spaces(target, after)
+}
+
+/// Writes a logfmt value, bare when possible and quoted when it would not
+/// survive splitting on spaces and `=`.
+fn write_logfmt_value<W: Write>(target: &mut W, value: &str) -> Result<(), std::io::Error> {
+    if logfmt_needs_quotes(value) {
+        write_quoted(target, value)
+    } else {
+        target.write_all(value.as_bytes())
+    }
+}
+
+fn logfmt_needs_quotes(value: &str) -> bool {
+    value.is_empty()
+        || value
+            .bytes()
+            .any(|b| b <= b' ' || b == b'=' || b == b'"' || b == b'\\' || b == 0x7f)
+}
+
+/// Writes a non-string field value, the same in logfmt and JSON.
+fn write_field_value<W: Write>(target: &mut W, value: &FieldValue) -> Result<(), std::io::Error> {
+    match value {
+        FieldValue::Str(value) => target.write_all(value.as_str().as_bytes()),
+        FieldValue::I64(value) => write!(target, "{}", value),
+        FieldValue::U64(value) => write!(target, "{}", value),
+        // `{:?}` keeps the fraction, so `1.0` does not read back as an integer
+        FieldValue::F64(value) => write!(target, "{:?}", value),
+        FieldValue::Bool(value) => write!(target, "{}", value),
+    }
+}
+
+/// Writes the `:line` half of a `file:line` location.
+fn write_line<W: Write>(target: &mut W, line: Option<u32>) -> Result<(), std::io::Error> {
+    match line {
+        Some(line) => write!(target, ":{}", line),
+        None => Ok(()),
+    }
+}
+
+/// Writes `value` as a double-quoted string with JSON escaping, which is also
+/// what logfmt parsers expect inside quotes.
+fn write_quoted<W: Write>(target: &mut W, value: &str) -> Result<(), std::io::Error> {
+    target.write_all(b"\"")?;
+    write_escaped(target, value, true)?;
+    target.write_all(b"\"")
+}
+
+/// Escapes backslashes, control characters and optionally double quotes,
+/// copying unescaped runs in one call.
+fn write_escaped<W: Write>(
+    target: &mut W,
+    value: &str,
+    quotes: bool,
+) -> Result<(), std::io::Error> {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+
+    let bytes = value.as_bytes();
+    let mut start = 0;
+    for (i, &byte) in bytes.iter().enumerate() {
+        let escaped: &[u8] = match byte {
+            b'"' if quotes => b"\\\"",
+            b'\\' => b"\\\\",
+            b'\n' => b"\\n",
+            b'\r' => b"\\r",
+            b'\t' => b"\\t",
+            0x00..=0x1f => {
+                if start < i {
+                    target.write_all(&bytes[start..i])?;
+                }
+                start = i + 1;
+                let unicode = [
+                    b'\\',
+                    b'u',
+                    b'0',
+                    b'0',
+                    HEX[(byte >> 4) as usize],
+                    HEX[(byte & 0xf) as usize],
+                ];
+                target.write_all(&unicode)?;
+                continue;
+            }
+            _ => continue,
+        };
+        if start < i {
+            target.write_all(&bytes[start..i])?;
+        }
+        start = i + 1;
+        target.write_all(escaped)?;
+    }
+    if start < bytes.len() {
+        target.write_all(&bytes[start..])?;
     }
-    target.write_all(b" msg=\"")?;
-    target.write_all(entry.msg().as_bytes())?;
-    target.write_all(b"\"\n")?;
     Ok(())
 }
 
-fn push_entry(tx: &Sender<Action>, entry: LogEntry) {
-    ENTRY_BUFFER.with(|buffer| {
-        let mut buffer = buffer.borrow_mut();
-        buffer.push(entry);
-        if buffer.len() >= DEFAULT_BATCH_SIZE {
-            let mut batch = Vec::with_capacity(DEFAULT_BATCH_SIZE);
-            std::mem::swap(&mut *buffer, &mut batch);
-            let _ = tx.send(Action::WriteBatch(batch));
+/// Runs `f` on the calling thread's batch for `queue`. Batches of queues with
+/// a latency bound are registered with the queue on first use, so the worker
+/// can ship them when idle.
+fn with_local_batch<R>(queue: &Queue, f: impl FnOnce(&mut Vec<LogEntry>) -> R) -> R {
+    if queue.state.max_latency.is_none() {
+        return ENTRY_BUFFER.with(|buffer| {
+            let mut buffer = buffer.borrow_mut();
+            let local = match &mut *buffer {
+                Some(local) if Arc::ptr_eq(&local.queue.state, &queue.state) => local,
+                // replacing sends what the previous queue still had buffered
+                slot => slot.insert(LocalBuffer {
+                    queue: queue.clone(),
+                    pending: Vec::with_capacity(DEFAULT_BATCH_SIZE),
+                }),
+            };
+            f(&mut local.pending)
+        });
+    }
+    ENTRY_BUFFERS.with(|buffers| {
+        let mut buffers = buffers.borrow_mut();
+        let index = match buffers.iter().position(|b| Arc::ptr_eq(&b.queue.state, &queue.state)) {
+            Some(index) => index,
+            None => {
+                buffers.retain(|b| !b.queue.state.closed.load(Ordering::Relaxed));
+                let batch = Arc::new(Mutex::new(Vec::with_capacity(DEFAULT_BATCH_SIZE)));
+                let mut registry = queue.state.batches.lock().unwrap();
+                registry.retain(|b| b.strong_count() > 0);
+                registry.push(Arc::downgrade(&batch));
+                buffers.push(ThreadBatch {
+                    queue: queue.clone(),
+                    batch,
+                });
+                buffers.len() - 1
+            }
+        };
+        let mut pending = buffers[index].batch.lock().unwrap();
+        f(&mut pending)
+    })
+}
+
+fn push_entry(queue: &Queue, entry: LogEntry, batch_size: usize) {
+    let max_latency = queue.state.max_latency;
+    with_local_batch(queue, |pending| {
+        let stale = match (max_latency, pending.first()) {
+            (Some(latency), Some(first)) => entry.ts.duration_since(first.ts) >= latency,
+            _ => false,
+        };
+        pending.push(entry);
+        if pending.len() >= batch_size || stale {
+            let batch = std::mem::replace(pending, Vec::with_capacity(batch_size));
+            queue.send_batch(batch);
         }
     });
 }
 
-fn flush_thread_buffer(tx: &Sender<Action>) {
-    ENTRY_BUFFER.with(|buffer| {
-        let mut buffer = buffer.borrow_mut();
-        if !buffer.is_empty() {
-            let mut batch = Vec::with_capacity(buffer.len());
-            std::mem::swap(&mut *buffer, &mut batch);
-            let _ = tx.send(Action::WriteBatch(batch));
+fn flush_thread_buffer(queue: &Queue) {
+    with_local_batch(queue, |pending| {
+        if !pending.is_empty() {
+            let batch = std::mem::take(pending);
+            queue.send_batch(batch);
         }
     });
 }
 
-pub fn init<P: ToString + Send + 'static>(name: &str, path: Option<P>, level: Level) -> Handle {
-    let (tx, rx) = crossbeam_channel::bounded(CHANNEL_CAPACITY);
+/// Why the global logger could not be installed.
+#[derive(Debug)]
+pub enum InitError {
+    /// Another logger is already installed with the `log` crate.
+    AlreadyInitialized,
+    /// The log file or its directory could not be opened.
+    OpenFile(std::io::Error),
+    /// The worker or compression thread could not be spawned.
+    SpawnThread(std::io::Error),
+}
 
-    let ctx = Context {
-        rx,
-        path,
-        date: Local::now().date_naive(),
-        unix_ts: false,
-    };
+impl std::fmt::Display for InitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InitError::AlreadyInitialized => f.write_str("a logger is already initialized"),
+            InitError::OpenFile(err) => write!(f, "cannot open log file: {}", err),
+            InitError::SpawnThread(err) => write!(f, "cannot spawn logger thread: {}", err),
+        }
+    }
+}
 
-    let logger = Logger {
-        tx: tx.clone(),
-        name: Some(Arc::from(name)),
-    };
+impl std::error::Error for InitError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            InitError::AlreadyInitialized => None,
+            InitError::OpenFile(err) | InitError::SpawnThread(err) => Some(err),
+        }
+    }
+}
 
-    log::set_boxed_logger(Box::new(logger)).expect("error to init logger");
-    log::set_max_level(level);
+/// Configures the global logger and its worker thread. Every option defaults to
+/// what [`init`] uses: logfmt to stdout, daily rotation, blocking on a full queue.
+///
+/// ```no_run
+/// use nexuslog::{Format, Level, NexusLogBuilder, Rotation};
+///
+/// let _handle = NexusLogBuilder::new("myapp")
+///     .path("/var/log/app.log")
+///     .level(Level::Debug)
+///     .format(Format::Json)
+///     .rotation(Rotation::Hourly)
+///     .build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct NexusLogBuilder {
+    name: String,
+    directives: Directives,
+    // the output configured on the builder itself, taking every record
+    sink: Sink,
+    sinks: Vec<Sink>,
+    capacity: usize,
+    batch_size: usize,
+    overflow: Overflow,
+    max_latency: Option<Duration>,
+}
 
-    let thread = std::thread::spawn(move || {
-        if let Err(msg) = worker(ctx) {
-            eprintln!("error {}", msg);
+impl NexusLogBuilder {
+    pub fn new(name: &str) -> Self {
+        NexusLogBuilder {
+            name: name.to_string(),
+            directives: Directives::new(Level::Info),
+            sink: Sink::stdout(),
+            sinks: Vec::new(),
+            capacity: CHANNEL_CAPACITY,
+            batch_size: DEFAULT_BATCH_SIZE,
+            overflow: Overflow::Block,
+            max_latency: None,
         }
-    });
+    }
+
+    /// Write to `path` instead of stdout. The file name gets a postfix per
+    /// rotation period.
+    pub fn path(mut self, path: impl ToString) -> Self {
+        self.sink.target = Target::File(path.to_string());
+        self
+    }
+
+    /// Write to stdout, the default.
+    pub fn stdout(mut self) -> Self {
+        self.sink.target = Target::Stdout;
+        self
+    }
+
+    /// Write to stderr, keeping stdout free for program output.
+    pub fn stderr(mut self) -> Self {
+        self.sink.target = Target::Stderr;
+        self
+    }
+
+    /// Default level for targets without a directive of their own.
+    pub fn level(mut self, level: Level) -> Self {
+        self.directives.default = level;
+        self
+    }
+
+    /// Per-target levels, replacing the ones set before.
+    ///
+    /// ```no_run
+    /// use nexuslog::{Directives, Level, NexusLogBuilder};
+    ///
+    /// let directives = Directives::from_env(Level::Info).unwrap_or(Directives::new(Level::Info));
+    /// let _handle = NexusLogBuilder::new("myapp").directives(directives).build();
+    /// ```
+    pub fn directives(mut self, directives: Directives) -> Self {
+        self.directives = directives;
+        self
+    }
+
+    pub fn format(mut self, format: Format) -> Self {
+        self.sink.format = format;
+        self
+    }
+
+    /// Write timestamps as unix nanoseconds instead of local RFC 3339 time.
+    pub fn unix_ts(mut self, unix_ts: bool) -> Self {
+        self.sink.unix_ts = unix_ts;
+        self
+    }
+
+    /// Escape quotes, backslashes and newlines in logfmt values, on by default.
+    pub fn escape(mut self, escape: bool) -> Self {
+        self.sink.escape = escape;
+        self
+    }
+
+    /// Write the record's target and source location as `target=` and
+    /// `loc=file:line` fields. Patterns use `{target}`, `{module}` and `{loc}`
+    /// instead.
+    pub fn location(mut self, location: bool) -> Self {
+        self.sink.location = location;
+        self
+    }
+
+    pub fn rotation(mut self, rotation: Rotation) -> Self {
+        self.sink.rotation = rotation;
+        self
+    }
+
+    /// Continue in a numbered file once the current one reaches `max_bytes`.
+    pub fn max_bytes(mut self, max_bytes: u64) -> Self {
+        self.sink.max_bytes = Some(max_bytes);
+        self
+    }
+
+    pub fn retention(mut self, retention: Retention) -> Self {
+        self.sink.retention = retention;
+        self
+    }
+
+    pub fn compression(mut self, compression: Compression) -> Self {
+        self.sink.compression = Some(compression);
+        self
+    }
+
+    /// Also write to `sink`, e.g. stdout at `Warn` next to a file at `Debug`.
+    /// Records pass the logger's directives first, then the sink's level. The
+    /// output settings of the builder only apply to its own output.
+    pub fn sink(mut self, sink: Sink) -> Self {
+        self.sinks.push(sink);
+        self
+    }
+
+    /// Number of batches the queue to the worker holds.
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Number of records a thread buffers before handing them to the worker.
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn overflow(mut self, overflow: Overflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Upper bound on how long a record may wait in a batch that is not full.
+    pub fn max_latency(mut self, max_latency: Duration) -> Self {
+        self.max_latency = Some(max_latency);
+        self
+    }
+
+    /// Installs the logger and starts the worker thread.
+    ///
+    /// # Panics
+    ///
+    /// If [`NexusLogBuilder::try_build`] fails.
+    pub fn build(self) -> Handle {
+        self.try_build()
+            .unwrap_or_else(|err| panic!("error to init logger: {}", err))
+    }
+
+    /// Opens the outputs, starts the worker thread and installs the logger,
+    /// leaving the global logger untouched on failure.
+    pub fn try_build(self) -> Result<Handle, InitError> {
+        let (queue, rx) = Queue::new(self.capacity, self.overflow, self.max_latency);
+        let ctx = Context {
+            rx,
+            queue: queue.clone(),
+        };
+
+        let mut sinks = self.sinks;
+        sinks.insert(0, self.sink);
+        let (sinks, compressor) = open_sinks(sinks)?;
+        let thread = std::thread::Builder::new()
+            .name("nexuslog".to_string())
+            .spawn(move || worker(ctx, sinks, compressor))
+            .map_err(InitError::SpawnThread)?;
+        // dropping it on failure stops the worker again
+        let handle = Handle {
+            tx: queue.tx.clone(),
+            thread: Some(thread),
+        };
+
+        let logger = Logger {
+            queue,
+            name: Some(Arc::from(self.name)),
+            batch_size: self.batch_size,
+            directives: self.directives.clone(),
+        };
+        log::set_boxed_logger(Box::new(logger)).map_err(|_| InitError::AlreadyInitialized)?;
+        log::set_max_level(self.directives.max_level());
+
+        Ok(handle)
+    }
+}
 
-    Handle {
-        tx,
-        thread: Some(thread),
+pub fn init<P: ToString + Send + 'static>(name: &str, path: Option<P>, level: Level) -> Handle {
+    builder(name, path, level).build()
+}
+
+/// Like [`init`], but returns an error instead of panicking.
+pub fn try_init<P: ToString + Send + 'static>(
+    name: &str,
+    path: Option<P>,
+    level: Level,
+) -> Result<Handle, InitError> {
+    builder(name, path, level).try_build()
+}
+
+/// Like [`init`], writing to stderr.
+pub fn init_stderr(name: &str, level: Level) -> Handle {
+    NexusLogBuilder::new(name).level(level).stderr().build()
+}
+
+fn builder<P: ToString>(name: &str, path: Option<P>, level: Level) -> NexusLogBuilder {
+    let builder = NexusLogBuilder::new(name).level(level);
+    match path {
+        Some(path) => builder.path(path),
+        None => builder,
     }
 }
 
@@ -445,38 +2565,30 @@ pub fn init<P: ToString + Send + 'static>(name: &str, path: Option<P>, level: Le
 #[cfg(feature = "python")]
 mod python {
     use super::{
-        cached_timestamp, flush_thread_buffer, worker, Action, Context, LogEntry,
-        LogMessage, LevelFilter, CHANNEL_CAPACITY, DEFAULT_BATCH_SIZE, INLINE_MSG_CAP,
+        cached_timestamp, flush_thread_buffer, Directives, open_sinks, push_entry, worker, Action,
+        Compression, Context, Field, FieldValue, Fields, Format, RECORD_KEYS, InlineStr, LevelFilter, LogEntry,
+        LogMessage, Overflow, Pattern, Queue, Retention, Rotation, Sink, Target, Timestamp,
+        CHANNEL_CAPACITY,
+        DEFAULT_BATCH_SIZE, INLINE_MSG_CAP,
     };
-    use chrono::Local;
-    use crossbeam_channel::Sender;
+    use pyo3::exceptions::{PyKeyError, PyRuntimeError, PyTypeError, PyValueError};
     use pyo3::prelude::*;
+    use pyo3::exceptions::PyBaseException;
+    use pyo3::types::{PyBool, PyDict, PyFloat, PyInt, PyList, PyMapping, PyString, PyTuple};
     use std::collections::HashMap;
     use std::hash::{Hash, Hasher};
-    use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
-    use std::sync::{Arc, Mutex, OnceLock, Weak};
+    use std::sync::atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex, OnceLock, RwLock, Weak};
     use std::thread::JoinHandle;
+    use std::time::Duration;
     use arrayvec::ArrayString;
 
     static BATCH_SIZE: AtomicUsize = AtomicUsize::new(DEFAULT_BATCH_SIZE);
 
-    fn push_entry(tx: &Sender<Action>, entry: LogEntry) {
-        use super::ENTRY_BUFFER;
-        let batch_size = BATCH_SIZE.load(Ordering::Relaxed);
-        ENTRY_BUFFER.with(|buffer| {
-            let mut buffer = buffer.borrow_mut();
-            buffer.push(entry);
-            if buffer.len() >= batch_size {
-                let mut batch = Vec::with_capacity(batch_size);
-                std::mem::swap(&mut *buffer, &mut batch);
-                let _ = tx.send(Action::WriteBatch(batch));
-            }
-        });
-    }
-
     #[derive(Clone, Eq)]
     enum PathKey {
         Stdout,
+        Stderr,
         File(String),
     }
 
@@ -484,6 +2596,7 @@ mod python {
         fn eq(&self, other: &Self) -> bool {
             match (self, other) {
                 (PathKey::Stdout, PathKey::Stdout) => true,
+                (PathKey::Stderr, PathKey::Stderr) => true,
                 (PathKey::File(a), PathKey::File(b)) => a == b,
                 _ => false,
             }
@@ -498,32 +2611,111 @@ mod python {
                     1u8.hash(state);
                     path.hash(state);
                 }
+                PathKey::Stderr => 2u8.hash(state),
+            }
+        }
+    }
+
+    impl PathKey {
+        /// A file name, `sys.stdout` or `sys.stderr`; `None` is stdout.
+        fn from_py(path: Option<&Bound<'_, PyAny>>) -> PyResult<Self> {
+            let Some(path) = path else {
+                return Ok(PathKey::Stdout);
+            };
+            if let Ok(path) = path.extract::<String>() {
+                return Ok(PathKey::File(path));
+            }
+            let sys = path.py().import("sys")?;
+            for (stream, key) in [
+                ("stdout", PathKey::Stdout),
+                ("__stdout__", PathKey::Stdout),
+                ("stderr", PathKey::Stderr),
+                ("__stderr__", PathKey::Stderr),
+            ] {
+                if path.is(&sys.getattr(stream)?) {
+                    return Ok(key);
+                }
+            }
+            Err(PyTypeError::new_err("path must be a str, sys.stdout or sys.stderr"))
+        }
+    }
+
+    impl From<PathKey> for Target {
+        fn from(key: PathKey) -> Self {
+            match key {
+                PathKey::Stdout => Target::Stdout,
+                PathKey::Stderr => Target::Stderr,
+                PathKey::File(path) => Target::File(path),
+            }
+        }
+    }
+
+    /// Settings `basic_config` applies to writers created afterwards.
+    #[derive(Clone)]
+    struct WriterOptions {
+        unix_ts: bool,
+        format: Format,
+        escape: bool,
+        max_bytes: Option<u64>,
+        retention: Retention,
+        compression: Option<Compression>,
+        rotation: Rotation,
+        overflow: Overflow,
+        max_latency: Option<Duration>,
+        // extra outputs of the writer for the default path
+        sinks: Vec<Sink>,
+    }
+
+    impl Default for WriterOptions {
+        fn default() -> Self {
+            WriterOptions {
+                unix_ts: false,
+                format: Format::Logfmt,
+                escape: true,
+                max_bytes: None,
+                retention: Retention::default(),
+                compression: None,
+                rotation: Rotation::Daily,
+                overflow: Overflow::Block,
+                max_latency: None,
+                sinks: Vec::new(),
             }
         }
     }
 
     struct SharedWriter {
-        tx: Sender<Action>,
+        queue: Queue,
         thread: Mutex<Option<JoinHandle<()>>>,
     }
 
     impl SharedWriter {
-        fn new(path: Option<String>, unix_ts: bool) -> Self {
-            let (tx, rx) = crossbeam_channel::bounded(CHANNEL_CAPACITY);
+        fn new(key: PathKey, options: WriterOptions) -> Self {
+            let (queue, rx) =
+                Queue::new(CHANNEL_CAPACITY, options.overflow, options.max_latency);
             let ctx = Context {
                 rx,
-                path,
-                date: Local::now().date_naive(),
-                unix_ts,
+                queue: queue.clone(),
             };
-            let thread = std::thread::spawn(move || {
-                if let Err(msg) = worker(ctx) {
-                    eprintln!("error {}", msg);
-                }
+            let mut sinks = vec![Sink {
+                target: key.into(),
+                level: LevelFilter::Trace,
+                format: options.format,
+                unix_ts: options.unix_ts,
+                escape: options.escape,
+                location: false,
+                rotation: options.rotation,
+                max_bytes: options.max_bytes,
+                retention: options.retention,
+                compression: options.compression,
+            }];
+            sinks.extend(options.sinks);
+            let thread = std::thread::spawn(move || match open_sinks(sinks) {
+                Ok((sinks, compressor)) => worker(ctx, sinks, compressor),
+                Err(err) => eprintln!("error {}", err),
             });
 
             SharedWriter {
-                tx,
+                queue,
                 thread: Mutex::new(Some(thread)),
             }
         }
@@ -531,7 +2723,7 @@ mod python {
         fn stop(&self) {
             let mut thread = self.thread.lock().unwrap();
             if let Some(thread) = thread.take() {
-                let _ = self.tx.send(Action::Exit);
+                self.queue.send(Action::Exit);
                 let _ = thread.join();
             }
         }
@@ -548,47 +2740,43 @@ mod python {
         REGISTRY.get_or_init(|| Mutex::new(HashMap::new()))
     }
 
-    fn default_path_cell() -> &'static OnceLock<Mutex<Option<String>>> {
-        static DEFAULT_PATH: OnceLock<Mutex<Option<String>>> = OnceLock::new();
+    fn default_path_cell() -> &'static OnceLock<Mutex<PathKey>> {
+        static DEFAULT_PATH: OnceLock<Mutex<PathKey>> = OnceLock::new();
         &DEFAULT_PATH
     }
 
-    fn default_unix_ts_cell() -> &'static OnceLock<Mutex<bool>> {
-        static DEFAULT_UNIX_TS: OnceLock<Mutex<bool>> = OnceLock::new();
-        &DEFAULT_UNIX_TS
+    fn default_options_cell() -> &'static OnceLock<Mutex<WriterOptions>> {
+        static DEFAULT_OPTIONS: OnceLock<Mutex<WriterOptions>> = OnceLock::new();
+        &DEFAULT_OPTIONS
     }
 
-    fn default_path() -> Option<String> {
+    fn default_path() -> PathKey {
         default_path_cell()
-            .get_or_init(|| Mutex::new(None))
+            .get_or_init(|| Mutex::new(PathKey::Stdout))
             .lock()
             .unwrap()
             .clone()
     }
 
-    fn default_unix_ts() -> bool {
-        *default_unix_ts_cell()
-            .get_or_init(|| Mutex::new(false))
+    fn default_options() -> WriterOptions {
+        default_options_cell()
+            .get_or_init(|| Mutex::new(WriterOptions::default()))
             .lock()
             .unwrap()
+            .clone()
     }
 
-    fn set_default_path(path: Option<String>) {
-        let cell = default_path_cell().get_or_init(|| Mutex::new(None));
+    fn set_default_path(path: PathKey) {
+        let cell = default_path_cell().get_or_init(|| Mutex::new(PathKey::Stdout));
         *cell.lock().unwrap() = path;
     }
 
-    fn set_default_unix_ts(unix_ts: bool) {
-        let cell = default_unix_ts_cell().get_or_init(|| Mutex::new(false));
-        *cell.lock().unwrap() = unix_ts;
+    fn set_default_options(options: WriterOptions) {
+        let cell = default_options_cell().get_or_init(|| Mutex::new(WriterOptions::default()));
+        *cell.lock().unwrap() = options;
     }
 
-    fn shared_writer(path: Option<String>) -> Arc<SharedWriter> {
-        let key = match path.clone() {
-            Some(p) => PathKey::File(p),
-            None => PathKey::Stdout,
-        };
-
+    fn shared_writer(key: PathKey) -> Arc<SharedWriter> {
         let mut map = registry().lock().unwrap();
         if let Some(weak) = map.get(&key) {
             if let Some(writer) = weak.upgrade() {
@@ -596,29 +2784,159 @@ mod python {
             }
         }
 
-        let writer = Arc::new(SharedWriter::new(path, default_unix_ts()));
-        map.insert(key, Arc::downgrade(&writer));
-        writer
+        let mut options = default_options();
+        if key != default_path() {
+            options.sinks.clear();
+        }
+        let writer = Arc::new(SharedWriter::new(key.clone(), options));
+        map.insert(key, Arc::downgrade(&writer));
+        writer
+    }
+
+    /// Levels of the named loggers, keyed by name with `""` for the root. A
+    /// logger without a level of its own takes the one of its closest dotted
+    /// ancestor (`http` for `http.client`), and finally the root's.
+    struct LevelTree {
+        nodes: HashMap<Arc<str>, LevelNode>,
+    }
+
+    struct LevelNode {
+        explicit: Option<u8>,
+        // effective level, shared with the cached logger
+        level: Arc<AtomicU8>,
+        cached: Option<Py<PyLogger>>,
+        // loggers created with `PyLogger(...)`, which keep their own level and
+        // are only reached by `set_level`
+        detached: Vec<Weak<AtomicU8>>,
+    }
+
+    impl LevelTree {
+        fn resolve(&self, mut name: &str) -> u8 {
+            loop {
+                if let Some(level) = self.nodes.get(name).and_then(|node| node.explicit) {
+                    return level;
+                }
+                if name.is_empty() {
+                    return level_to_u8(PyLevel::Info);
+                }
+                name = name.rfind('.').map_or("", |dot| &name[..dot]);
+            }
+        }
+
+        fn node(&mut self, name: &str) -> &mut LevelNode {
+            if !self.nodes.contains_key(name) {
+                let level = self.resolve(name);
+                self.nodes.insert(
+                    Arc::from(name),
+                    LevelNode {
+                        explicit: None,
+                        level: Arc::new(AtomicU8::new(level)),
+                        cached: None,
+                        detached: Vec::new(),
+                    },
+                );
+            }
+            self.nodes.get_mut(name).unwrap()
+        }
+
+        /// Sets the level of `name` and passes it on to descendants without a
+        /// level of their own.
+        fn set(&mut self, name: &str, level: u8) {
+            let node = self.node(name);
+            node.explicit = Some(level);
+            node.detached.retain(|weak| match weak.upgrade() {
+                Some(detached) => {
+                    detached.store(level, Ordering::Relaxed);
+                    true
+                }
+                None => false,
+            });
+            self.refresh();
+        }
+
+        fn refresh(&self) {
+            for (name, node) in &self.nodes {
+                node.level.store(self.resolve(name), Ordering::Relaxed);
+            }
+        }
+
+        /// Forgets all levels and cached loggers, for a new `basic_config`.
+        /// Returns the loggers so they are dropped after the lock is released.
+        fn reset(&mut self) -> Vec<Py<PyLogger>> {
+            let cached = self
+                .nodes
+                .values_mut()
+                .filter_map(|node| {
+                    node.explicit = None;
+                    node.cached.take()
+                })
+                .collect();
+            self.refresh();
+            cached
+        }
+    }
+
+    fn level_tree() -> &'static Mutex<LevelTree> {
+        static LEVELS: OnceLock<Mutex<LevelTree>> = OnceLock::new();
+        LEVELS.get_or_init(|| {
+            Mutex::new(LevelTree {
+                nodes: HashMap::new(),
+            })
+        })
+    }
+
+    fn u8_to_level(level: u8) -> PyLevel {
+        match level {
+            0 => PyLevel::Critical,
+            1 => PyLevel::Error,
+            2 => PyLevel::Warn,
+            3 => PyLevel::Info,
+            4 => PyLevel::Debug,
+            _ => PyLevel::Trace,
+        }
     }
 
-    fn level_to_u8(level: log::Level) -> u8 {
+    fn level_to_u8(level: PyLevel) -> u8 {
         match level {
-            log::Level::Error => 1,
-            log::Level::Warn => 2,
-            log::Level::Info => 3,
-            log::Level::Debug => 4,
-            log::Level::Trace => 5,
+            PyLevel::Critical => 0,
+            PyLevel::Error => 1,
+            PyLevel::Warn => 2,
+            PyLevel::Info => 3,
+            PyLevel::Debug => 4,
+            PyLevel::Trace => 5,
         }
     }
 
-    #[pyclass]
-    #[derive(Clone, Copy)]
+    /// `Critical` has no counterpart in the `log` crate. It is written as an
+    /// error record flagged critical, shown as `level=critical`.
+    #[pyclass(eq)]
+    #[derive(Clone, Copy, PartialEq, Eq)]
     pub enum PyLevel {
         Trace,
         Debug,
         Info,
         Warn,
         Error,
+        Critical,
+    }
+
+    impl PyLevel {
+        /// Accepts a `PyLevel` or a stdlib numeric level, rounded down to the
+        /// nearest known one (`logging.INFO + 5` is info).
+        fn from_py(level: &Bound<'_, PyAny>) -> PyResult<Self> {
+            if let Ok(level) = level.extract::<PyLevel>() {
+                return Ok(level);
+            }
+            match level.extract::<i64>() {
+                Ok(50..) => Ok(PyLevel::Critical),
+                Ok(40..) => Ok(PyLevel::Error),
+                Ok(30..) => Ok(PyLevel::Warn),
+                Ok(20..) => Ok(PyLevel::Info),
+                Ok(10..) => Ok(PyLevel::Debug),
+                Ok(_) => Ok(PyLevel::Trace),
+                Err(_) => Err(PyTypeError::new_err("level must be a Level or an int")),
+            }
+        }
     }
 
     impl From<PyLevel> for LevelFilter {
@@ -628,7 +2946,7 @@ mod python {
                 PyLevel::Debug => LevelFilter::Debug,
                 PyLevel::Info => LevelFilter::Info,
                 PyLevel::Warn => LevelFilter::Warn,
-                PyLevel::Error => LevelFilter::Error,
+                PyLevel::Error | PyLevel::Critical => LevelFilter::Error,
             }
         }
     }
@@ -640,8 +2958,131 @@ mod python {
                 PyLevel::Debug => log::Level::Debug,
                 PyLevel::Info => log::Level::Info,
                 PyLevel::Warn => log::Level::Warn,
-                PyLevel::Error => log::Level::Error,
+                PyLevel::Error | PyLevel::Critical => log::Level::Error,
+            }
+        }
+    }
+
+    #[pyclass]
+    #[derive(Clone, Copy)]
+    pub enum PyFormat {
+        Logfmt,
+        Json,
+    }
+
+    impl From<PyFormat> for Format {
+        fn from(format: PyFormat) -> Self {
+            match format {
+                PyFormat::Logfmt => Format::Logfmt,
+                PyFormat::Json => Format::Json,
+            }
+        }
+    }
+
+    #[pyclass]
+    #[derive(Clone, Copy)]
+    pub enum PyCompression {
+        Gzip,
+        Zstd,
+    }
+
+    impl TryFrom<PyCompression> for Compression {
+        type Error = PyErr;
+
+        fn try_from(compression: PyCompression) -> PyResult<Self> {
+            match compression {
+                #[cfg(feature = "gzip")]
+                PyCompression::Gzip => Ok(Compression::Gzip),
+                #[cfg(feature = "zstd")]
+                PyCompression::Zstd => Ok(Compression::Zstd),
+                #[allow(unreachable_patterns)]
+                _ => Err(PyValueError::new_err(
+                    "compression is not available in this build",
+                )),
+            }
+        }
+    }
+
+    #[pyclass]
+    #[derive(Clone, Copy)]
+    pub enum PyRotation {
+        Never,
+        Minutely,
+        Hourly,
+        Daily,
+        Weekly,
+    }
+
+    impl PyRotation {
+        fn to_rotation(self, minutes: u32) -> Rotation {
+            match self {
+                PyRotation::Never => Rotation::Never,
+                PyRotation::Minutely => Rotation::Minutes(minutes.max(1)),
+                PyRotation::Hourly => Rotation::Hourly,
+                PyRotation::Daily => Rotation::Daily,
+                PyRotation::Weekly => Rotation::Weekly,
+            }
+        }
+    }
+
+    #[pyclass]
+    #[derive(Clone, Copy)]
+    pub enum PyOverflow {
+        Block,
+        DropNewest,
+        DropOldest,
+    }
+
+    impl From<PyOverflow> for Overflow {
+        fn from(overflow: PyOverflow) -> Self {
+            match overflow {
+                PyOverflow::Block => Overflow::Block,
+                PyOverflow::DropNewest => Overflow::DropNewest,
+                PyOverflow::DropOldest => Overflow::DropOldest,
+            }
+        }
+    }
+
+    /// An extra output of the default writer, passed to `basic_config`. File
+    /// sinks take rotation, size and retention settings from `basic_config`.
+    #[pyclass]
+    #[derive(Clone)]
+    pub struct PySink {
+        sink: Sink,
+    }
+
+    #[pymethods]
+    impl PySink {
+        #[new]
+        #[pyo3(signature = (path=None, level=None, fmt=PyFormat::Logfmt, pattern=None, unix_ts=false, escape=true))]
+        fn new(
+            path: Option<&Bound<'_, PyAny>>,
+            level: Option<&Bound<'_, PyAny>>,
+            fmt: PyFormat,
+            pattern: Option<&str>,
+            unix_ts: bool,
+            escape: bool,
+        ) -> PyResult<Self> {
+            let mut sink = Sink {
+                target: PathKey::from_py(path)?.into(),
+                ..Sink::stdout()
             }
+            .format(parse_format(fmt, pattern)?)
+            .unix_ts(unix_ts)
+            .escape(escape);
+            if let Some(level) = level {
+                sink = sink.level(PyLevel::from_py(level)?.into());
+            }
+            Ok(PySink { sink })
+        }
+    }
+
+    fn parse_format(fmt: PyFormat, pattern: Option<&str>) -> PyResult<Format> {
+        match pattern {
+            Some(pattern) => Ok(Format::Pattern(
+                Pattern::parse(pattern).map_err(|e| PyValueError::new_err(e.to_string()))?,
+            )),
+            None => Ok(fmt.into()),
         }
     }
 
@@ -649,102 +3090,963 @@ mod python {
     pub struct PyLogger {
         writer: Arc<SharedWriter>,
         name: Option<Arc<str>>,
-        level: AtomicU8,
+        // effective level, kept up to date by `LevelTree`
+        level: Arc<AtomicU8>,
+        // cached by `get_logger`, so its level is part of the hierarchy
+        inherits: bool,
     }
 
     #[pymethods]
     impl PyLogger {
         #[new]
-        #[pyo3(signature = (name, path=None, level=PyLevel::Info))]
-        fn new(name: Option<String>, path: Option<String>, level: PyLevel) -> PyResult<Self> {
-            Ok(PyLogger {
-                writer: shared_writer(path),
-                name: name.map(Arc::from),
-                level: AtomicU8::new(level_to_u8(level.into())),
-            })
+        #[pyo3(signature = (name, path=None, level=None))]
+        fn new(
+            name: Option<String>,
+            path: Option<&Bound<'_, PyAny>>,
+            level: Option<&Bound<'_, PyAny>>,
+        ) -> PyResult<Self> {
+            PyLogger::with_writer(shared_writer(PathKey::from_py(path)?), name, level)
+        }
+
+        #[pyo3(name = "setLevel")]
+        fn set_level(&self, level: &Bound<'_, PyAny>) -> PyResult<()> {
+            let level = level_to_u8(PyLevel::from_py(level)?);
+            if self.inherits {
+                level_tree().lock().unwrap().set(self.name.as_deref().unwrap_or(""), level);
+            } else {
+                self.level.store(level, Ordering::Relaxed);
+            }
+            Ok(())
+        }
+
+        #[pyo3(name = "getEffectiveLevel")]
+        fn get_effective_level(&self) -> PyLevel {
+            u8_to_level(self.level.load(Ordering::Relaxed))
+        }
+
+        #[pyo3(name = "isEnabledFor")]
+        fn is_enabled_for(&self, level: &Bound<'_, PyAny>) -> PyResult<bool> {
+            let level = PyLevel::from_py(level)?;
+            Ok(level_to_u8(level) <= self.level.load(Ordering::Relaxed))
         }
 
         fn shutdown(&self) {
-            flush_thread_buffer(&self.writer.tx);
-            let _ = self.writer.tx.send(Action::Flush);
+            flush_thread_buffer(&self.writer.queue);
+            self.writer.queue.flush();
             if Arc::strong_count(&self.writer) == 1 {
                 self.writer.stop();
             }
         }
 
-        fn trace(&self, message: &str) {
-            self.log_internal(log::Level::Trace, message);
+        #[pyo3(signature = (message, *args, exc_info=None, stack_info=false, extra=None, **kwargs))]
+        fn trace(
+            &self,
+            message: &Bound<'_, PyAny>,
+            args: &Bound<'_, PyTuple>,
+            exc_info: Option<&Bound<'_, PyAny>>,
+            stack_info: bool,
+            extra: Option<&Bound<'_, PyDict>>,
+            kwargs: Option<&Bound<'_, PyDict>>,
+        ) -> PyResult<()> {
+            self.log_internal(PyLevel::Trace, message, args, exc_info, stack_info, extra, kwargs)
+        }
+
+        #[pyo3(signature = (message, *args, exc_info=None, stack_info=false, extra=None, **kwargs))]
+        fn debug(
+            &self,
+            message: &Bound<'_, PyAny>,
+            args: &Bound<'_, PyTuple>,
+            exc_info: Option<&Bound<'_, PyAny>>,
+            stack_info: bool,
+            extra: Option<&Bound<'_, PyDict>>,
+            kwargs: Option<&Bound<'_, PyDict>>,
+        ) -> PyResult<()> {
+            self.log_internal(PyLevel::Debug, message, args, exc_info, stack_info, extra, kwargs)
         }
 
-        fn debug(&self, message: &str) {
-            self.log_internal(log::Level::Debug, message);
+        #[pyo3(signature = (message, *args, exc_info=None, stack_info=false, extra=None, **kwargs))]
+        fn info(
+            &self,
+            message: &Bound<'_, PyAny>,
+            args: &Bound<'_, PyTuple>,
+            exc_info: Option<&Bound<'_, PyAny>>,
+            stack_info: bool,
+            extra: Option<&Bound<'_, PyDict>>,
+            kwargs: Option<&Bound<'_, PyDict>>,
+        ) -> PyResult<()> {
+            self.log_internal(PyLevel::Info, message, args, exc_info, stack_info, extra, kwargs)
         }
 
-        fn info(&self, message: &str) {
-            self.log_internal(log::Level::Info, message);
+        #[pyo3(signature = (message, *args, exc_info=None, stack_info=false, extra=None, **kwargs))]
+        fn warn(
+            &self,
+            message: &Bound<'_, PyAny>,
+            args: &Bound<'_, PyTuple>,
+            exc_info: Option<&Bound<'_, PyAny>>,
+            stack_info: bool,
+            extra: Option<&Bound<'_, PyDict>>,
+            kwargs: Option<&Bound<'_, PyDict>>,
+        ) -> PyResult<()> {
+            self.log_internal(PyLevel::Warn, message, args, exc_info, stack_info, extra, kwargs)
         }
 
-        fn warn(&self, message: &str) {
-            self.log_internal(log::Level::Warn, message);
+        #[pyo3(signature = (message, *args, exc_info=None, stack_info=false, extra=None, **kwargs))]
+        fn error(
+            &self,
+            message: &Bound<'_, PyAny>,
+            args: &Bound<'_, PyTuple>,
+            exc_info: Option<&Bound<'_, PyAny>>,
+            stack_info: bool,
+            extra: Option<&Bound<'_, PyDict>>,
+            kwargs: Option<&Bound<'_, PyDict>>,
+        ) -> PyResult<()> {
+            self.log_internal(PyLevel::Error, message, args, exc_info, stack_info, extra, kwargs)
         }
 
-        fn error(&self, message: &str) {
-            self.log_internal(log::Level::Error, message);
+        /// `error()` with the exception being handled attached.
+        #[pyo3(signature = (message, *args, exc_info=None, stack_info=false, extra=None, **kwargs))]
+        fn exception(
+            &self,
+            message: &Bound<'_, PyAny>,
+            args: &Bound<'_, PyTuple>,
+            exc_info: Option<&Bound<'_, PyAny>>,
+            stack_info: bool,
+            extra: Option<&Bound<'_, PyDict>>,
+            kwargs: Option<&Bound<'_, PyDict>>,
+        ) -> PyResult<()> {
+            let exc_info = match exc_info {
+                Some(exc_info) => exc_info.clone(),
+                None => PyBool::new(message.py(), true).to_owned().into_any(),
+            };
+            let exc_info = Some(&exc_info);
+            self.log_internal(PyLevel::Error, message, args, exc_info, stack_info, extra, kwargs)
+        }
+
+        #[pyo3(signature = (message, *args, exc_info=None, stack_info=false, extra=None, **kwargs))]
+        fn critical(
+            &self,
+            message: &Bound<'_, PyAny>,
+            args: &Bound<'_, PyTuple>,
+            exc_info: Option<&Bound<'_, PyAny>>,
+            stack_info: bool,
+            extra: Option<&Bound<'_, PyDict>>,
+            kwargs: Option<&Bound<'_, PyDict>>,
+        ) -> PyResult<()> {
+            self.log_internal(PyLevel::Critical, message, args, exc_info, stack_info, extra, kwargs)
+        }
+
+        /// Logs at `level`, a `PyLevel` or a stdlib numeric level.
+        #[pyo3(signature = (level, message, *args, exc_info=None, stack_info=false, extra=None, **kwargs))]
+        #[allow(clippy::too_many_arguments)]
+        fn log(
+            &self,
+            level: &Bound<'_, PyAny>,
+            message: &Bound<'_, PyAny>,
+            args: &Bound<'_, PyTuple>,
+            exc_info: Option<&Bound<'_, PyAny>>,
+            stack_info: bool,
+            extra: Option<&Bound<'_, PyDict>>,
+            kwargs: Option<&Bound<'_, PyDict>>,
+        ) -> PyResult<()> {
+            let level = PyLevel::from_py(level)?;
+            self.log_internal(level, message, args, exc_info, stack_info, extra, kwargs)
         }
     }
 
     impl PyLogger {
+        fn with_writer(
+            writer: Arc<SharedWriter>,
+            name: Option<String>,
+            level: Option<&Bound<'_, PyAny>>,
+        ) -> PyResult<Self> {
+            let level = match level {
+                Some(level) => PyLevel::from_py(level)?,
+                None => PyLevel::Info,
+            };
+            let level = Arc::new(AtomicU8::new(level_to_u8(level)));
+            let mut tree = level_tree().lock().unwrap();
+            let node = tree.node(name.as_deref().unwrap_or(""));
+            node.detached.retain(|weak| weak.strong_count() > 0);
+            node.detached.push(Arc::downgrade(&level));
+            Ok(PyLogger {
+                writer,
+                name: name.map(Arc::from),
+                level,
+                inherits: false,
+            })
+        }
+
         #[inline]
-        fn log_internal(&self, level: log::Level, message: &str) {
+        #[allow(clippy::too_many_arguments)]
+        fn log_internal(
+            &self,
+            level: PyLevel,
+            message: &Bound<'_, PyAny>,
+            args: &Bound<'_, PyTuple>,
+            exc_info: Option<&Bound<'_, PyAny>>,
+            stack_info: bool,
+            extra: Option<&Bound<'_, PyDict>>,
+            kwargs: Option<&Bound<'_, PyDict>>,
+        ) -> PyResult<()> {
             let max_level = self.level.load(Ordering::Relaxed);
             if level_to_u8(level) <= max_level {
-                let msg = {
-                    let mut inline = ArrayString::<INLINE_MSG_CAP>::new();
-                    if inline.try_push_str(message).is_ok() {
-                        LogMessage::Inline(inline)
-                    } else {
-                        LogMessage::Heap(message.to_owned())
-                    }
+                let py = message.py();
+                // like the stdlib, a bad `%` argument is reported, not raised
+                let (message, format_error) = match format_message(message, args) {
+                    Ok(formatted) => (formatted, None),
+                    Err(err) => (message.str().or_else(|_| message.repr())?, Some(err.to_string())),
                 };
+                let message = message.to_str()?;
+                let mut fields = collect_fields(extra, kwargs)?;
+                if let Some(err) = format_error {
+                    fields.push_str("format_error", err);
+                }
+                if let Some(exc_info) = exc_info {
+                    if let Some(exc) = format_exc_info(exc_info)? {
+                        fields.push_str("exc", exc);
+                    }
+                }
+                if stack_info {
+                    fields.push_str("stack", format_stack(py)?);
+                }
                 let entry = LogEntry {
                     ts: cached_timestamp(),
                     name: self.name.as_ref().map(Arc::clone),
-                    level,
-                    msg,
+                    level: level.into(),
+                    critical: matches!(level, PyLevel::Critical),
+                    msg: log_message(message),
+                    location: None,
+                    fields,
+                };
+                push_entry(&self.writer.queue, entry, BATCH_SIZE.load(Ordering::Relaxed));
+            }
+            Ok(())
+        }
+    }
+
+    /// Writes stdlib `logging.LogRecord`s through a `SharedWriter`, so records
+    /// of libraries using the standard library end up in the same output.
+    /// Filtering is left to the stdlib logger and handler levels.
+    #[pyclass]
+    pub struct PyHandler {
+        writer: Arc<SharedWriter>,
+    }
+
+    #[pymethods]
+    impl PyHandler {
+        #[new]
+        #[pyo3(signature = (path=None))]
+        fn new(path: Option<&Bound<'_, PyAny>>) -> PyResult<Self> {
+            let key = match path {
+                Some(path) => PathKey::from_py(Some(path))?,
+                None => default_path(),
+            };
+            Ok(PyHandler {
+                writer: shared_writer(key),
+            })
+        }
+
+        fn emit(&self, record: &Bound<'_, PyAny>) -> PyResult<()> {
+            let level = PyLevel::from_py(&record.getattr("levelno")?)?;
+            let message = record.call_method0("getMessage")?.str()?;
+            let name = record.getattr("name")?.str()?;
+
+            let mut fields = Fields::default();
+            let exc_text = record.getattr("exc_text")?;
+            if exc_text.is_truthy()? {
+                fields.push_str("exc", exc_text.str()?.extract()?);
+            } else if let Some(exc) = format_exc_info(&record.getattr("exc_info")?)? {
+                fields.push_str("exc", exc);
+            }
+            let stack_info = record.getattr("stack_info")?;
+            if stack_info.is_truthy()? {
+                fields.push_str("stack", stack_info.str()?.extract()?);
+            }
+
+            let created = Duration::try_from_secs_f64(record.getattr("created")?.extract()?)
+                .unwrap_or_default();
+            let entry = LogEntry {
+                ts: Timestamp {
+                    secs: created.as_secs(),
+                    nanos: created.subsec_nanos(),
+                },
+                name: Some(Arc::from(name.to_str()?)),
+                level: level.into(),
+                critical: matches!(level, PyLevel::Critical),
+                msg: log_message(message.to_str()?),
+                location: None,
+                fields,
+            };
+            push_entry(&self.writer.queue, entry, BATCH_SIZE.load(Ordering::Relaxed));
+            Ok(())
+        }
+
+        fn flush(&self) {
+            flush_thread_buffer(&self.writer.queue);
+            self.writer.queue.flush();
+        }
+    }
+
+    /// `log` crate backend installed by `forward_rust_log`, so `log::info!` in
+    /// Rust code of the extension goes to the writer of the Python loggers.
+    struct RustLogBridge(RwLock<Option<RustLogTarget>>);
+
+    struct RustLogTarget {
+        writer: Arc<SharedWriter>,
+        directives: Directives,
+    }
+
+    static RUST_LOG_BRIDGE: RustLogBridge = RustLogBridge(RwLock::new(None));
+    static RUST_LOG_INSTALLED: AtomicBool = AtomicBool::new(false);
+
+    impl log::Log for RustLogBridge {
+        fn enabled(&self, metadata: &log::Metadata) -> bool {
+            match &*self.0.read().unwrap() {
+                Some(target) => metadata.level() <= target.directives.level(metadata.target()),
+                None => false,
+            }
+        }
+
+        fn log(&self, record: &log::Record) {
+            let target = self.0.read().unwrap();
+            if let Some(target) = &*target {
+                if record.level() <= target.directives.level(record.target()) {
+                    // named after the Rust target, e.g. `mycrate::db`
+                    let entry = LogEntry::from_record(record, Some(Arc::from(record.target())));
+                    push_entry(&target.writer.queue, entry, BATCH_SIZE.load(Ordering::Relaxed));
+                }
+            }
+        }
+
+        fn flush(&self) {
+            if let Some(target) = &*self.0.read().unwrap() {
+                flush_thread_buffer(&target.writer.queue);
+                target.writer.queue.flush();
+            }
+        }
+    }
+
+    impl RustLogBridge {
+        /// Points the bridge at the current default writer, after `basic_config`.
+        fn retarget(&self) {
+            let mut target = self.0.write().unwrap();
+            if let Some(target) = &mut *target {
+                target.writer = shared_writer(default_path());
+            }
+        }
+    }
+
+    fn log_message(message: &str) -> LogMessage {
+        let mut inline = ArrayString::<INLINE_MSG_CAP>::new();
+        if inline.try_push_str(message).is_ok() {
+            LogMessage::Inline(inline)
+        } else {
+            LogMessage::Heap(message.to_owned())
+        }
+    }
+
+    /// Renders `str(message) % args` like the stdlib does, so callers can pass
+    /// arguments instead of formatting records that end up disabled.
+    fn format_message<'py>(
+        message: &Bound<'py, PyAny>,
+        args: &Bound<'py, PyTuple>,
+    ) -> PyResult<Bound<'py, PyString>> {
+        let message = message.str()?;
+        if args.is_empty() {
+            return Ok(message);
+        }
+        // a single non-empty mapping is used for `%(key)s` lookups
+        let args = match args.len() {
+            1 => {
+                let arg = args.get_item(0)?;
+                if arg.cast::<PyMapping>().is_ok() && arg.is_truthy()? {
+                    arg
+                } else {
+                    args.clone().into_any()
+                }
+            }
+            _ => args.clone().into_any(),
+        };
+        message.rem(args)?.str()
+    }
+
+    /// Formats `exc_info` like the stdlib: an exception instance, a
+    /// `sys.exc_info()` tuple, or any other true value for the exception being
+    /// handled. `None` when there is nothing to format.
+    fn format_exc_info(exc_info: &Bound<'_, PyAny>) -> PyResult<Option<String>> {
+        if !exc_info.is_truthy()? {
+            return Ok(None);
+        }
+        let py = exc_info.py();
+        let (kind, value, tb) = if exc_info.is_instance_of::<PyBaseException>() {
+            (
+                exc_info.get_type().into_any(),
+                exc_info.clone(),
+                exc_info.getattr("__traceback__")?,
+            )
+        } else if exc_info.is_instance_of::<PyTuple>() {
+            exc_info.extract()?
+        } else {
+            py.import("sys")?.call_method0("exc_info")?.extract()?
+        };
+        if kind.is_none() {
+            return Ok(None);
+        }
+        let lines = py
+            .import("traceback")?
+            .call_method1("format_exception", (kind, value, tb))?;
+        Ok(Some(join_lines(&lines)?))
+    }
+
+    /// The caller's stack in the stdlib's `stack_info` layout, without the
+    /// frames of the `nexuslog` facade.
+    fn format_stack(py: Python<'_>) -> PyResult<String> {
+        let traceback = py.import("traceback")?;
+        let frames = traceback.call_method0("extract_stack")?.cast_into::<PyList>()?;
+        if let Ok(facade) = py.import("nexuslog").and_then(|m| m.getattr("__file__")) {
+            while let Some(last) = frames.len().checked_sub(1) {
+                if !frames.get_item(last)?.getattr("filename")?.eq(&facade)? {
+                    break;
+                }
+                frames.del_item(last)?;
+            }
+        }
+        let lines = traceback.call_method1("format_list", (frames,))?;
+        Ok(format!("Stack (most recent call last):\n{}", join_lines(&lines)?))
+    }
+
+    fn join_lines(lines: &Bound<'_, PyAny>) -> PyResult<String> {
+        let text = PyString::new(lines.py(), "").call_method1("join", (lines,))?;
+        Ok(text.extract::<String>()?.trim_end_matches('\n').to_string())
+    }
+
+    /// Turns `extra={...}` and keyword arguments into record fields. Ints,
+    /// floats, strings and booleans keep their type, anything else is `str()`-ed.
+    /// A key the record already has raises `KeyError`, like the stdlib does
+    /// for `extra`.
+    fn collect_fields(
+        extra: Option<&Bound<'_, PyDict>>,
+        kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<Fields> {
+        let mut fields = Fields::default();
+        for (dict, keywords) in [(extra, false), (kwargs, true)] {
+            let Some(dict) = dict else {
+                continue;
+            };
+            for (key, value) in dict.iter() {
+                let key = match key.cast::<PyString>() {
+                    Ok(key) => InlineStr::new(key.to_str()?),
+                    Err(_) => InlineStr::new(key.str()?.to_str()?),
                 };
-                push_entry(&self.writer.tx, entry);
+                // stdlib keyword, records carry no Python location to adjust
+                if keywords && key.as_str() == "stacklevel" {
+                    continue;
+                }
+                if RECORD_KEYS.contains(&key.as_str())
+                    || fields.iter().any(|field| field.key.as_str() == key.as_str())
+                {
+                    return Err(PyKeyError::new_err(format!(
+                        "Attempt to overwrite '{}' in LogRecord",
+                        key.as_str()
+                    )));
+                }
+                fields.push(Field {
+                    key,
+                    value: field_value(&value)?,
+                });
+            }
+        }
+        Ok(fields)
+    }
+
+    fn field_value(value: &Bound<'_, PyAny>) -> PyResult<FieldValue> {
+        // bool first, it is a subclass of int
+        if let Ok(value) = value.cast::<PyBool>() {
+            return Ok(FieldValue::Bool(value.is_true()));
+        }
+        if let Ok(value) = value.cast::<PyInt>() {
+            if let Ok(value) = value.extract::<i64>() {
+                return Ok(FieldValue::I64(value));
             }
+            if let Ok(value) = value.extract::<u64>() {
+                return Ok(FieldValue::U64(value));
+            }
+        } else if let Ok(value) = value.cast::<PyFloat>() {
+            return Ok(FieldValue::F64(value.value()));
+        } else if let Ok(value) = value.cast::<PyString>() {
+            return Ok(FieldValue::Str(InlineStr::new(value.to_str()?)));
         }
+        Ok(FieldValue::Str(InlineStr::new(value.str()?.to_str()?)))
     }
 
     #[pymodule]
     #[pyo3(name = "_logger")]
     pub fn logger_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
         #[pyfunction]
-        #[pyo3(signature = (path=None, unix_ts=false, batch_size=None))]
-        fn basic_config(path: Option<String>, unix_ts: bool, batch_size: Option<usize>) -> PyResult<()> {
-            set_default_path(path);
-            set_default_unix_ts(unix_ts);
+        #[pyo3(signature = (
+            path=None,
+            unix_ts=false,
+            batch_size=None,
+            fmt=PyFormat::Logfmt,
+            escape=true,
+            pattern=None,
+            max_bytes=None,
+            max_files=None,
+            max_age_days=None,
+            max_total_bytes=None,
+            compression=None,
+            rotation=PyRotation::Daily,
+            rotation_minutes=1,
+            overflow=PyOverflow::Block,
+            max_latency_ms=None,
+            sinks=None,
+        ))]
+        #[allow(clippy::too_many_arguments)]
+        fn basic_config(
+            path: Option<&Bound<'_, PyAny>>,
+            unix_ts: bool,
+            batch_size: Option<usize>,
+            fmt: PyFormat,
+            escape: bool,
+            pattern: Option<&str>,
+            max_bytes: Option<u64>,
+            max_files: Option<usize>,
+            max_age_days: Option<u32>,
+            max_total_bytes: Option<u64>,
+            compression: Option<PyCompression>,
+            rotation: PyRotation,
+            rotation_minutes: u32,
+            overflow: PyOverflow,
+            max_latency_ms: Option<u64>,
+            sinks: Option<Vec<PySink>>,
+        ) -> PyResult<()> {
+            let compression = compression.map(Compression::try_from).transpose()?;
+            let format = parse_format(fmt, pattern)?;
+            let max_bytes = max_bytes.filter(|&n| n > 0);
+            let retention = Retention {
+                max_files: max_files.map(|n| n.max(1)),
+                max_age_days,
+                max_total_bytes,
+            };
+            let rotation = rotation.to_rotation(rotation_minutes);
+            let sinks = sinks
+                .unwrap_or_default()
+                .into_iter()
+                .map(|py_sink| Sink {
+                    rotation,
+                    max_bytes,
+                    retention,
+                    compression,
+                    ..py_sink.sink
+                })
+                .collect();
+            let cached = level_tree().lock().unwrap().reset();
+            drop(cached);
+            set_default_path(PathKey::from_py(path)?);
+            set_default_options(WriterOptions {
+                unix_ts,
+                format,
+                escape,
+                max_bytes,
+                retention,
+                compression,
+                rotation,
+                overflow: overflow.into(),
+                max_latency: max_latency_ms.map(Duration::from_millis),
+                sinks,
+            });
             if let Some(size) = batch_size {
                 BATCH_SIZE.store(size.max(1), Ordering::Relaxed);
             }
+            RUST_LOG_BRIDGE.retarget();
             Ok(())
         }
 
+        /// Installs a `log` crate backend writing Rust records to the default
+        /// output, next to the Python ones. `level` is a Level, a stdlib number
+        /// or directives like `"info,hyper=warn"`; without it `NEXUSLOG` or
+        /// `RUST_LOG` is read. Calling it again replaces the levels.
         #[pyfunction]
-        #[pyo3(signature = (name, level=PyLevel::Info))]
-        fn get_logger(name: Option<String>, level: PyLevel) -> PyResult<PyLogger> {
-            Ok(PyLogger {
+        #[pyo3(signature = (level=None))]
+        fn forward_rust_log(level: Option<&Bound<'_, PyAny>>) -> PyResult<()> {
+            let directives = match level {
+                Some(level) => match level.extract::<String>() {
+                    Ok(spec) => Directives::parse(&spec),
+                    Err(_) => Ok(Directives::new(PyLevel::from_py(level)?.into())),
+                },
+                None => Directives::from_env(LevelFilter::Info),
+            }
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+            let max_level = directives.max_level();
+            *RUST_LOG_BRIDGE.0.write().unwrap() = Some(RustLogTarget {
                 writer: shared_writer(default_path()),
-                name: name.map(Arc::from),
-                level: AtomicU8::new(level_to_u8(level.into())),
-            })
+                directives,
+            });
+            if !RUST_LOG_INSTALLED.swap(true, Ordering::SeqCst)
+                && log::set_logger(&RUST_LOG_BRIDGE).is_err()
+            {
+                RUST_LOG_INSTALLED.store(false, Ordering::SeqCst);
+                *RUST_LOG_BRIDGE.0.write().unwrap() = None;
+                return Err(PyRuntimeError::new_err("another log backend is already installed"));
+            }
+            log::set_max_level(max_level);
+            Ok(())
+        }
+
+        /// Returns the logger cached for `name`, creating it on first use. The
+        /// root logger is `None` or `""`. A `level` becomes the logger's own,
+        /// otherwise it inherits from its dotted ancestors.
+        #[pyfunction]
+        #[pyo3(signature = (name, level=None))]
+        fn get_logger(
+            py: Python<'_>,
+            name: Option<String>,
+            level: Option<&Bound<'_, PyAny>>,
+        ) -> PyResult<Py<PyLogger>> {
+            let level = level.map(PyLevel::from_py).transpose()?.map(level_to_u8);
+            let key = name.as_deref().unwrap_or("");
+            let shared = {
+                let mut tree = level_tree().lock().unwrap();
+                if let Some(level) = level {
+                    tree.set(key, level);
+                }
+                let node = tree.node(key);
+                if let Some(logger) = &node.cached {
+                    return Ok(logger.clone_ref(py));
+                }
+                Arc::clone(&node.level)
+            };
+
+            // created without holding the lock, allocating may run Python code
+            let logger = Py::new(
+                py,
+                PyLogger {
+                    writer: shared_writer(default_path()),
+                    name: name.as_deref().filter(|name| !name.is_empty()).map(Arc::from),
+                    level: shared,
+                    inherits: true,
+                },
+            )?;
+            let mut tree = level_tree().lock().unwrap();
+            let node = tree.node(key);
+            Ok(node.cached.get_or_insert(logger).clone_ref(py))
+        }
+
+        /// Sets the level of the logger called `name`, which its descendants
+        /// without a level of their own follow, and of every `Logger(name)`.
+        #[pyfunction]
+        fn set_level(name: Option<String>, level: &Bound<'_, PyAny>) -> PyResult<()> {
+            let level = level_to_u8(PyLevel::from_py(level)?);
+            level_tree()
+                .lock()
+                .unwrap()
+                .set(name.as_deref().unwrap_or(""), level);
+            Ok(())
+        }
+
+        /// Writes out what this thread buffered for every writer and waits for
+        /// the workers, the package runs it at interpreter exit.
+        #[pyfunction]
+        fn flush_all() {
+            let writers: Vec<_> = registry()
+                .lock()
+                .unwrap()
+                .values()
+                .filter_map(Weak::upgrade)
+                .collect();
+            for writer in writers {
+                flush_thread_buffer(&writer.queue);
+                writer.queue.flush();
+            }
         }
 
         m.add_class::<PyLevel>()?;
+        m.add_class::<PyFormat>()?;
+        m.add_class::<PyCompression>()?;
+        m.add_class::<PyRotation>()?;
+        m.add_class::<PyOverflow>()?;
         m.add_class::<PyLogger>()?;
+        m.add_class::<PyHandler>()?;
+        m.add_class::<PySink>()?;
         m.add_function(wrap_pyfunction!(basic_config, m)?)?;
         m.add_function(wrap_pyfunction!(get_logger, m)?)?;
+        m.add_function(wrap_pyfunction!(set_level, m)?)?;
+        m.add_function(wrap_pyfunction!(forward_rust_log, m)?)?;
+        m.add_function(wrap_pyfunction!(flush_all, m)?)?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn directives_per_target() {
+        let directives = Directives::parse("info,hyper=warn,mycrate::db=trace").unwrap();
+        assert_eq!(directives.level("hyper"), LevelFilter::Warn);
+        assert_eq!(directives.level("hyper::client"), LevelFilter::Warn);
+        assert_eq!(directives.level("mycrate::db"), LevelFilter::Trace);
+        assert_eq!(directives.level("mycrate::db::pool"), LevelFilter::Trace);
+        assert_eq!(directives.level("mycrate"), LevelFilter::Info);
+        assert_eq!(directives.level("other"), LevelFilter::Info);
+        assert_eq!(directives.max_level(), LevelFilter::Trace);
+    }
+
+    #[test]
+    fn directives_bare_target_enables_everything() {
+        let directives = Directives::parse("mycrate").unwrap();
+        assert_eq!(directives.level("mycrate"), LevelFilter::Trace);
+        assert_eq!(directives.level("other"), LevelFilter::Info);
+    }
+
+    #[test]
+    fn directives_last_duplicate_wins() {
+        let directives = Directives::parse("hyper=warn,hyper=error").unwrap();
+        assert_eq!(directives.level("hyper"), LevelFilter::Error);
+        assert_eq!(directives.targets.len(), 1);
+    }
+
+    #[test]
+    fn directives_invalid() {
+        assert!(Directives::parse("hyper=loud").is_err());
+        assert!(Directives::parse("=warn").is_err());
+    }
+
+    #[test]
+    fn directives_match_whole_path_segments() {
+        let directives = Directives::parse("hyper=warn").unwrap();
+        assert_eq!(directives.level("hyperx"), LevelFilter::Info);
+        assert_eq!(directives.level("hyper::x"), LevelFilter::Warn);
+    }
+
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> Result<usize, std::io::Error> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> Result<(), std::io::Error> {
+            Ok(())
+        }
+    }
+
+    /// Writes `record` to `sink` and returns the output.
+    fn render(sink: Sink, record: &Record) -> String {
+        let captured = Captured::default();
+        let mut active = ActiveSink::open(sink).unwrap();
+        active.output = Output::console(Box::new(captured.clone()));
+
+        let entry = LogEntry::from_record(record, None);
+        let mut sinks = [active];
+        write_entry(&mut sinks, None, &entry);
+        flush_sinks(&mut sinks);
+
+        let output = captured.0.lock().unwrap();
+        String::from_utf8(output.clone()).unwrap()
+    }
+
+    /// Writes a record carrying a field of every value type to `sink`.
+    fn render_fields(sink: Sink) -> String {
+        let fields: [(&str, Value); 5] = [
+            ("n", Value::from(3)),
+            ("ratio", Value::from(0.5)),
+            ("ok", Value::from(true)),
+            ("user", Value::from("a b")),
+            ("nan", Value::from(f64::NAN)),
+        ];
+        render(
+            sink,
+            &Record::builder()
+                .args(format_args!("hi"))
+                .level(log::Level::Info)
+                .key_values(&fields)
+                .build(),
+        )
+    }
+
+    #[test]
+    fn fields_in_logfmt() {
+        let line = render_fields(Sink::stdout());
+        assert!(
+            line.ends_with(" level=info msg=\"hi\" n=3 ratio=0.5 ok=true user=\"a b\" nan=NaN\n"),
+            "{line}"
+        );
+    }
+
+    #[test]
+    fn fields_in_json() {
+        let line = render_fields(Sink::stdout().format(Format::Json));
+        assert!(
+            line.ends_with(
+                "\"level\":\"info\",\"msg\":\"hi\",\"n\":3,\"ratio\":0.5,\"ok\":true,\"user\":\"a b\",\"nan\":null}\n"
+            ),
+            "{line}"
+        );
+    }
+
+    /// Writes a record logged at `src/db.rs:42` in `mycrate::db` to `sink`.
+    fn render_located(sink: Sink, target: &str) -> String {
+        render(
+            sink,
+            &Record::builder()
+                .args(format_args!("hi"))
+                .level(log::Level::Info)
+                .target(target)
+                .module_path_static(Some("mycrate::db"))
+                .file_static(Some("src/db.rs"))
+                .line(Some(42))
+                .build(),
+        )
+    }
+
+    #[test]
+    fn location_in_logfmt() {
+        let line = render_located(Sink::stdout().location(true), "mycrate::db");
+        assert!(
+            line.ends_with(" level=info target=mycrate::db loc=src/db.rs:42 msg=\"hi\"\n"),
+            "{line}"
+        );
+        let line = render_located(Sink::stdout(), "mycrate::db");
+        assert!(line.ends_with(" level=info msg=\"hi\"\n"), "{line}");
+    }
+
+    #[test]
+    fn location_in_json() {
+        let line = render_located(Sink::stdout().format(Format::Json).location(true), "mycrate::db");
+        assert!(
+            line.ends_with("\"level\":\"info\",\"target\":\"mycrate::db\",\"loc\":\"src/db.rs:42\",\"msg\":\"hi\"}\n"),
+            "{line}"
+        );
+        let line = render_located(Sink::stdout().format(Format::Json), "mycrate::db");
+        assert!(line.ends_with("\"level\":\"info\",\"msg\":\"hi\"}\n"), "{line}");
+    }
+
+    #[test]
+    fn location_in_pattern() {
+        let pattern = Pattern::parse("{target} {module} {loc} {msg}").unwrap();
+        let line = render_located(Sink::stdout().format(Format::Pattern(pattern)), "mycrate::db");
+        assert_eq!(line, "mycrate::db mycrate::db src/db.rs:42 hi\n");
+    }
+
+    #[test]
+    fn explicit_target_is_copied() {
+        let record = Record::builder()
+            .target("audit")
+            .module_path_static(Some("mycrate::db"))
+            .build();
+        assert!(matches!(Location::from_record(&record).target, Cow::Owned(_)));
+
+        let line = render_located(Sink::stdout().location(true), "audit");
+        assert!(line.contains(" target=audit loc=src/db.rs:42 "), "{line}");
+        let pattern = Pattern::parse("{target} {module}").unwrap();
+        let line = render_located(Sink::stdout().format(Format::Pattern(pattern)), "audit");
+        assert_eq!(line, "audit mycrate::db\n");
+    }
+
+    #[test]
+    fn fields_never_repeat_record_keys() {
+        let fields: [(&str, Value); 2] = [("msg", Value::from("override")), ("level", Value::from(3))];
+        let line = render(
+            Sink::stdout().format(Format::Json),
+            &Record::builder()
+                .args(format_args!("hello"))
+                .level(log::Level::Info)
+                .key_values(&fields)
+                .build(),
+        );
+        assert!(
+            line.ends_with(
+                "\"level\":\"info\",\"msg\":\"hello\",\"fields.msg\":\"override\",\"fields.level\":3}\n"
+            ),
+            "{line}"
+        );
+    }
+
+    struct Broken;
+
+    impl Write for Broken {
+        fn write(&mut self, _: &[u8]) -> Result<usize, std::io::Error> {
+            Err(std::io::Error::other("disk full"))
+        }
+
+        fn flush(&mut self) -> Result<(), std::io::Error> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn failing_sink_leaves_others_running() {
+        let captured = Captured::default();
+        let mut broken = ActiveSink::open(Sink::stdout()).unwrap();
+        broken.output = Output::console(Box::new(Broken));
+        let mut working = ActiveSink::open(Sink::stdout()).unwrap();
+        working.output = Output::console(Box::new(captured.clone()));
+        let mut sinks = [broken, working];
+
+        for msg in ["first", "second"] {
+            let entry = LogEntry::from_record(
+                &Record::builder()
+                    .args(format_args!("{msg}"))
+                    .level(log::Level::Info)
+                    .build(),
+                None,
+            );
+            write_entry(&mut sinks, None, &entry);
+            flush_sinks(&mut sinks);
+        }
+
+        assert!(sinks[0].retry_at.is_some());
+        assert!(sinks[1].retry_at.is_none());
+        let output = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        assert_eq!(output.lines().count(), 2, "{output}");
+    }
+
+    #[test]
+    fn failed_sink_is_reopened_after_delay() {
+        let dir = std::env::temp_dir().join(format!("nexuslog-retry-{}", std::process::id()));
+        let path = dir.join("app.log");
+        let mut active = ActiveSink::open(Sink::file(path.display()).rotation(Rotation::Never)).unwrap();
+        active.output = Output::console(Box::new(Broken));
+        let mut sinks = [active];
+
+        for msg in ["lost", "skipped", "kept"] {
+            let entry = LogEntry::from_record(
+                &Record::builder()
+                    .args(format_args!("{msg}"))
+                    .level(log::Level::Info)
+                    .build(),
+                None,
+            );
+            write_entry(&mut sinks, None, &entry);
+            flush_sinks(&mut sinks);
+            if msg == "skipped" {
+                assert_eq!(sinks[0].retry_delay, RETRY_DELAY_MIN * 2);
+                // pretend the delay has passed
+                sinks[0].retry_at = Some(Instant::now());
+            }
+        }
+
+        assert!(sinks[0].retry_at.is_none());
+        assert_eq!(sinks[0].retry_delay, RETRY_DELAY_MIN);
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(!contents.contains("lost") && !contents.contains("skipped"), "{contents}");
+        assert!(contents.contains("msg=\"kept\""), "{contents}");
+    }
+
+    // the only test installing the global logger
+    #[test]
+    fn try_init_twice() {
+        let Ok(mut handle) = try_init("first", None::<String>, Level::Info) else {
+            panic!("first logger not installed");
+        };
+
+        let second = try_init("second", None::<String>, Level::Info);
+        assert!(matches!(second, Err(InitError::AlreadyInitialized)));
+        handle.stop();
+    }
+}